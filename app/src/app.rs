@@ -2,10 +2,21 @@ use std::time::Duration;
 
 use crate::{
     main_schedules::*,
-    prelude::Textures,
+    particles::update_particle_emitters,
+    prelude::{
+        AnimationFinished, Diagnostics, Fonts, FrameRecorder, GpuTimings, LightSettings2d,
+        PostProcesses, Screenshot, ShaderHotReload, Shaders, TextureError, Textures,
+    },
+    sprite_animation::advance_sprite_animations,
+    texture_loader::{finish_texture_loads, reload_modified_textures},
     update_render_state::{self, update_render_state},
 };
-use bevy_ecs::{prelude::*, schedule::ScheduleLabel, system::ScheduleSystem};
+use bevy_ecs::{
+    event::{EventRegistry, event_update_system},
+    prelude::*,
+    schedule::ScheduleLabel,
+    system::ScheduleSystem,
+};
 use render::{
     prelude::*,
     winit::{error::EventLoopError, window::WindowAttributes},
@@ -14,6 +25,7 @@ use render::{
 pub struct App {
     world: World,
     window_attributes: Option<WindowAttributes>,
+    shader_hot_reload: Option<ShaderHotReload>,
     main_schedule_order: MainScheduleOrder,
 }
 
@@ -24,12 +36,34 @@ impl App {
 
         update_render_state::init(&mut world);
         world.init_resource::<Textures>();
+        world.init_resource::<Shaders>();
+        world.init_resource::<Fonts>();
+        world.init_resource::<PostProcesses>();
+        world.init_resource::<LightSettings2d>();
+        world.init_resource::<FrameRecorder>();
+        world.init_resource::<GpuTimings>();
+        world.init_resource::<Diagnostics>();
 
-        Self {
+        let mut app = Self {
             world,
             window_attributes: None,
+            shader_hot_reload: None,
             main_schedule_order: MainScheduleOrder::default(),
-        }
+        };
+
+        app.add_event::<AnimationFinished>();
+        app.add_event::<Screenshot>();
+        app.add_event::<TextureError>();
+        app.add_systems(
+            PreUpdate,
+            (
+                (reload_modified_textures, finish_texture_loads).chain(),
+                advance_sprite_animations,
+                update_particle_emitters,
+            ),
+        );
+
+        app
     }
 
     /// Adds the given systems to the schedule.
@@ -44,14 +78,25 @@ impl App {
             .add_systems(systems);
     }
 
+    /// Registers an event type so its events are kept for one frame before being dropped.
+    pub fn add_event<T: Event>(&mut self) {
+        EventRegistry::register_event::<T>(&mut self.world);
+    }
+
     /// Runs the app.
     pub fn run(&mut self) -> Result<(), EventLoopError> {
         for &label in &self.main_schedule_order.startup {
             let _ = self.world.try_run_schedule(label);
         }
 
-        let render = |delta_time: Duration, render_pipeline: &mut RenderPipeline| {
+        let render = |frame_time: Duration, render_pipeline: &mut RenderPipeline| {
+            // Recordings simulate a fixed frame rate regardless of how long frames take
+            let delta_time = match self.world.resource::<FrameRecorder>().get_recording() {
+                Some(recording) => recording.step,
+                None => frame_time,
+            };
             self.world.insert_resource(DeltaTime(delta_time));
+            let _ = self.world.run_system_cached(event_update_system);
 
             for &label in &self.main_schedule_order.before_state_update {
                 let _ = self.world.try_run_schedule(label);
@@ -60,15 +105,31 @@ impl App {
             let update_render_state = update_render_state(render_pipeline, &mut self.world);
             render_pipeline.render(update_render_state);
 
+            let render_stats = render_pipeline.get_render_stats();
+            self.world
+                .resource_mut::<Diagnostics>()
+                .record_frame(frame_time, render_stats);
+
+            let supported = render_pipeline.is_gpu_timing_supported();
+            let gpu_timings = render_pipeline.take_gpu_timings();
+            let mut gpu_timings_resource = self.world.resource_mut::<GpuTimings>();
+            gpu_timings_resource.supported = supported;
+            if let Some(gpu_timings) = gpu_timings {
+                gpu_timings_resource.passes = gpu_timings;
+            }
+
             for &label in &self.main_schedule_order.after_state_update {
                 let _ = self.world.try_run_schedule(label);
             }
 
+            // Unused assets are freed at the end of the frame so handles dropped this frame count
+            self.world.resource_mut::<Textures>().free_unused();
             self.world.clear_trackers();
         };
 
         RenderApp::new(render)
             .with_window_attributes(self.window_attributes.take())
+            .with_shader_hot_reload(self.shader_hot_reload.take())
             .run_app()
     }
 
@@ -77,6 +138,26 @@ impl App {
         self.window_attributes = window_attributes;
         self
     }
+
+    /// Sets if textures loaded with `Textures::load` are reloaded when their files are modified.
+    /// (Meant for development, texture files are checked for modification twice a second)
+    pub fn with_texture_hot_reload(mut self, texture_hot_reload: bool) -> Self {
+        self.world.resource_mut::<Textures>().loader.hot_reload = texture_hot_reload;
+        self
+    }
+
+    /// Sets the shader hot-reloading settings. (Meant for development, shader files are checked
+    /// for modification twice a second)
+    pub fn with_shader_hot_reload(mut self, shader_hot_reload: Option<ShaderHotReload>) -> Self {
+        self.shader_hot_reload = shader_hot_reload;
+        self
+    }
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Clone, Copy, Resource)]
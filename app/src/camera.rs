@@ -1,8 +1,10 @@
 use bevy_ecs::prelude::*;
 use bevy_transform::components::Transform;
-use render::wgpu;
+use render::{glam::Mat4, wgpu};
 
-/// A world-space camera. (Currently only one camera is supported)
+use crate::prelude::{DenseStorageIndex, Texture};
+
+/// A world-space camera. (Only one camera can draw to the window)
 #[derive(Clone, Copy, Component)]
 #[require(Transform)]
 pub struct Camera {
@@ -10,4 +12,63 @@ pub struct Camera {
     pub near_clip: f32,
     pub far_clip: f32,
     pub clear_color: wgpu::Color,
+    /// The texture to draw to or `None` to draw to the window. The texture is drawn before the
+    /// window so materials can show it in the same frame, its data is ignored and its size is the
+    /// resolution.
+    pub target: Option<DenseStorageIndex<Texture>>,
+}
+
+/// A screen-space camera that draws the `ScreenSpace` entities on top of the world camera. One
+/// world unit is one window pixel and x goes right and y goes up from the origin corner.
+/// (Currently only one UI camera is supported)
+#[derive(Clone, Copy, Component)]
+pub struct UiCamera {
+    pub origin: ScreenOrigin,
+    pub near_clip: f32,
+    pub far_clip: f32,
 }
+
+impl UiCamera {
+    /// Creates a new `UiCamera` that draws z values from -1000 to 1000.
+    pub fn new(origin: ScreenOrigin) -> Self {
+        Self {
+            origin,
+            near_clip: -1000.0,
+            far_clip: 1000.0,
+        }
+    }
+
+    /// Gets the projection for a window size in pixels.
+    pub(crate) fn projection(&self, width: f32, height: f32) -> Mat4 {
+        let (left, right) = match self.origin {
+            ScreenOrigin::BottomLeft | ScreenOrigin::TopLeft => (0.0, width),
+            ScreenOrigin::BottomRight | ScreenOrigin::TopRight => (-width, 0.0),
+        };
+        let (bottom, top) = match self.origin {
+            ScreenOrigin::BottomLeft | ScreenOrigin::BottomRight => (0.0, height),
+            ScreenOrigin::TopLeft | ScreenOrigin::TopRight => (-height, 0.0),
+        };
+
+        Mat4::orthographic_rh(left, right, bottom, top, self.near_clip, self.far_clip)
+    }
+}
+
+impl Default for UiCamera {
+    fn default() -> Self {
+        Self::new(ScreenOrigin::default())
+    }
+}
+
+/// The window corner at the origin of a `UiCamera`.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenOrigin {
+    #[default]
+    BottomLeft,
+    BottomRight,
+    TopLeft,
+    TopRight,
+}
+
+/// Draws the entity with the `UiCamera` instead of the world `Camera`.
+#[derive(Default, Clone, Copy, Component)]
+pub struct ScreenSpace;
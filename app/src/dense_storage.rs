@@ -1,6 +1,8 @@
 use std::{
     hash::{Hash, Hasher},
     marker::PhantomData,
+    ops::{Index, IndexMut},
+    sync::{Arc, Weak},
 };
 
 /// Stores the dense storage index and generational index.
@@ -33,12 +35,124 @@ impl<T> Hash for DenseStorageIndex<T> {
     }
 }
 
+/// A handle that keeps its value alive while it or a clone of it exists. (Values pushed with
+/// `DenseStorage::push_strong` are freed once they have no strong handles left)
+#[derive(Debug)]
+pub struct StrongHandle<T> {
+    index: DenseStorageIndex<T>,
+    count: Arc<()>,
+}
+
+impl<T> StrongHandle<T> {
+    pub fn index(&self) -> DenseStorageIndex<T> {
+        self.index
+    }
+
+    /// Gets a weak handle to the same value.
+    pub fn downgrade(&self) -> WeakHandle<T> {
+        self.index
+    }
+
+    /// Gets the number of strong handles to the value.
+    pub fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.count)
+    }
+}
+
+impl<T> Clone for StrongHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index,
+            count: self.count.clone(),
+        }
+    }
+}
+impl<T> Eq for StrongHandle<T> {}
+impl<T> PartialEq for StrongHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+impl<T> Hash for StrongHandle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+/// A handle that doesn't keep its value alive. (Getting the value fails once it's freed)
+pub type WeakHandle<T> = DenseStorageIndex<T>;
+
+/// A strong or weak handle to a value in a `DenseStorage`.
+#[derive(Debug)]
+pub enum Handle<T> {
+    Strong(StrongHandle<T>),
+    Weak(WeakHandle<T>),
+}
+
+impl<T> Handle<T> {
+    pub fn index(&self) -> DenseStorageIndex<T> {
+        match self {
+            Handle::Strong(handle) => handle.index,
+            Handle::Weak(index) => *index,
+        }
+    }
+
+    pub fn is_strong(&self) -> bool {
+        matches!(self, Handle::Strong(_))
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Handle::Strong(handle) => Handle::Strong(handle.clone()),
+            Handle::Weak(index) => Handle::Weak(*index),
+        }
+    }
+}
+impl<T> Eq for Handle<T> {}
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index() == other.index()
+    }
+}
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index().hash(state);
+    }
+}
+
+impl<T> From<StrongHandle<T>> for Handle<T> {
+    fn from(handle: StrongHandle<T>) -> Self {
+        Handle::Strong(handle)
+    }
+}
+impl<T> From<&StrongHandle<T>> for Handle<T> {
+    fn from(handle: &StrongHandle<T>) -> Self {
+        Handle::Strong(handle.clone())
+    }
+}
+impl<T> From<DenseStorageIndex<T>> for Handle<T> {
+    fn from(index: DenseStorageIndex<T>) -> Self {
+        Handle::Weak(index)
+    }
+}
+
 /// A generational index storage container.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "serde_impls::SerializedDenseStorage<T>")
+)]
 pub struct DenseStorage<T> {
     // (generation, value)
     storage: Vec<(u32, Option<T>)>,
     recycled_indices: Vec<usize>,
+    /// The strong handle counts of values pushed with `push_strong` by index. (Not serialized,
+    /// deserialized values aren't reference-counted)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    strong_counts: Vec<Option<Weak<()>>>,
 }
 
 impl<T> DenseStorage<T> {
@@ -47,9 +161,40 @@ impl<T> DenseStorage<T> {
         Self {
             storage: Vec::new(),
             recycled_indices: Vec::new(),
+            strong_counts: Vec::new(),
         }
     }
 
+    /// Creates a new empty container with space for at least `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            storage: Vec::with_capacity(capacity),
+            recycled_indices: Vec::new(),
+            strong_counts: Vec::new(),
+        }
+    }
+
+    /// Reserves space for at least `additional` more values.
+    pub fn reserve(&mut self, additional: usize) {
+        let additional = additional.saturating_sub(self.recycled_indices.len());
+        self.storage.reserve(additional);
+    }
+
+    /// Gets the number of values the container can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.storage.capacity()
+    }
+
+    /// Gets the number of values in the container.
+    pub fn len(&self) -> usize {
+        // Every empty slot is recycled
+        self.storage.len() - self.recycled_indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Pushes a new value into the container and returns a `DenseStorageIndex` that can be used to
     /// retrieve the value later.
     pub fn push(&mut self, value: T) -> DenseStorageIndex<T> {
@@ -63,6 +208,58 @@ impl<T> DenseStorage<T> {
         }
     }
 
+    /// Pushes a new reference-counted value into the container and returns a `StrongHandle` to it.
+    /// (The value is freed by `remove_unused` once it has no strong handles left)
+    pub fn push_strong(&mut self, value: T) -> StrongHandle<T> {
+        let index = self.push(value);
+        let count = Arc::new(());
+
+        if self.strong_counts.len() <= index.0 {
+            self.strong_counts.resize(index.0 + 1, None);
+        }
+        self.strong_counts[index.0] = Some(Arc::downgrade(&count));
+
+        StrongHandle { index, count }
+    }
+
+    /// Gets a new strong handle to a reference-counted value or `None` if the value doesn't exist,
+    /// isn't reference-counted or has no strong handles left.
+    pub fn upgrade(&self, index: WeakHandle<T>) -> Option<StrongHandle<T>> {
+        self.get(index)?;
+        let count = self.strong_counts.get(index.0)?.as_ref()?.upgrade()?;
+
+        Some(StrongHandle { index, count })
+    }
+
+    /// Gets the number of strong handles to a reference-counted value or `None` if the value
+    /// doesn't exist or isn't reference-counted.
+    pub fn strong_count(&self, index: DenseStorageIndex<T>) -> Option<usize> {
+        self.get(index)?;
+        let count = self.strong_counts.get(index.0)?.as_ref()?;
+
+        Some(count.strong_count())
+    }
+
+    /// Removes the reference-counted values without strong handles and returns them.
+    pub fn remove_unused(&mut self) -> Vec<(DenseStorageIndex<T>, T)> {
+        let unused: Vec<_> = self
+            .strong_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| {
+                count
+                    .as_ref()
+                    .is_some_and(|count| count.strong_count() == 0)
+            })
+            .map(|(i, _)| DenseStorageIndex::new(i, self.storage[i].0))
+            .collect();
+
+        unused
+            .into_iter()
+            .filter_map(|index| self.remove(index).map(|value| (index, value)))
+            .collect()
+    }
+
     /// Gets a value from the container with the given index or `None` if the value doesn't
     /// exist.
     pub fn get(&self, index: DenseStorageIndex<T>) -> Option<&T> {
@@ -72,23 +269,97 @@ impl<T> DenseStorage<T> {
             .and_then(|(_, value)| value.as_ref())
     }
 
+    /// Checks if a value exists at the given index.
+    pub fn contains(&self, index: DenseStorageIndex<T>) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Gets a mutable value from the container with the given index or `None` if the value
+    /// doesn't exist.
+    pub fn get_mut(&mut self, index: DenseStorageIndex<T>) -> Option<&mut T> {
+        self.storage
+            .get_mut(index.0)
+            .filter(|(generation, _)| *generation == index.1)
+            .and_then(|(_, value)| value.as_mut())
+    }
+
     /// Removes a value from the container with the given index and returns the value if it exists.
     pub fn remove(&mut self, index: DenseStorageIndex<T>) -> Option<T> {
-        if let Some((generation, value)) = self.storage.get_mut(index.0) {
-            if value.is_some() {
-                *generation += 1;
-                self.recycled_indices.push(index.0);
-            }
+        let (generation, value) = self
+            .storage
+            .get_mut(index.0)
+            .filter(|(generation, _)| *generation == index.1)?;
+        let value = value.take()?;
 
-            value.take()
-        } else {
-            None
+        *generation += 1;
+        self.recycled_indices.push(index.0);
+        if let Some(count) = self.strong_counts.get_mut(index.0) {
+            *count = None;
+        }
+
+        Some(value)
+    }
+
+    /// Removes the values `keep` returns `false` for. (Their indices are invalidated)
+    pub fn retain(&mut self, mut keep: impl FnMut(DenseStorageIndex<T>, &mut T) -> bool) {
+        let removed: Vec<_> = self
+            .iter_mut()
+            .filter_map(|(index, value)| (!keep(index, value)).then_some(index))
+            .collect();
+
+        for index in removed {
+            self.remove(index);
         }
     }
 
+    /// Removes all values and returns them. (Their indices are invalidated)
+    pub fn drain(&mut self) -> std::vec::IntoIter<(DenseStorageIndex<T>, T)> {
+        let indices: Vec<_> = self.iter().map(|(index, _)| index).collect();
+
+        indices
+            .into_iter()
+            .filter_map(|index| self.remove(index).map(|value| (index, value)))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Removes all values. (Their indices are invalidated, unlike creating a new container)
+    pub fn clear(&mut self) {
+        self.drain();
+    }
+
     pub fn iter(&self) -> DenseStorageIter<'_, T> {
         self.into_iter()
     }
+
+    pub fn iter_mut(&mut self) -> DenseStorageIterMut<'_, T> {
+        self.into_iter()
+    }
+}
+
+impl<T> Index<DenseStorageIndex<T>> for DenseStorage<T> {
+    type Output = T;
+
+    /// Gets a value from the container with the given index. (Panics if the value doesn't exist)
+    fn index(&self, index: DenseStorageIndex<T>) -> &Self::Output {
+        self.get(index)
+            .expect("no value exists at the dense storage index")
+    }
+}
+
+impl<T> IndexMut<DenseStorageIndex<T>> for DenseStorage<T> {
+    /// Gets a mutable value from the container with the given index. (Panics if the value doesn't
+    /// exist)
+    fn index_mut(&mut self, index: DenseStorageIndex<T>) -> &mut Self::Output {
+        self.get_mut(index)
+            .expect("no value exists at the dense storage index")
+    }
+}
+
+impl<T> Default for DenseStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 type DenseStorageIter<'a, T> = std::iter::FilterMap<
@@ -96,6 +367,11 @@ type DenseStorageIter<'a, T> = std::iter::FilterMap<
     fn((usize, &(u32, Option<T>))) -> Option<(DenseStorageIndex<T>, &T)>,
 >;
 
+type DenseStorageIterMut<'a, T> = std::iter::FilterMap<
+    std::iter::Enumerate<std::slice::IterMut<'a, (u32, Option<T>)>>,
+    fn((usize, &mut (u32, Option<T>))) -> Option<(DenseStorageIndex<T>, &mut T)>,
+>;
+
 type DenseStorageIntoIter<T> = std::iter::FilterMap<
     std::iter::Enumerate<std::vec::IntoIter<(u32, Option<T>)>>,
     fn((usize, (u32, Option<T>))) -> Option<(DenseStorageIndex<T>, T)>,
@@ -117,6 +393,22 @@ impl<'a, T> IntoIterator for &'a DenseStorage<T> {
     }
 }
 
+impl<'a, T> IntoIterator for &'a mut DenseStorage<T> {
+    type Item = (DenseStorageIndex<T>, &'a mut T);
+    type IntoIter = DenseStorageIterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.storage
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, (generation, value))| {
+                value
+                    .as_mut()
+                    .map(|value| (DenseStorageIndex::new(i, *generation), value))
+            })
+    }
+}
+
 impl<T> IntoIterator for DenseStorage<T> {
     type Item = (DenseStorageIndex<T>, T);
     type IntoIter = DenseStorageIntoIter<T>;
@@ -130,3 +422,341 @@ impl<T> IntoIterator for DenseStorage<T> {
             })
     }
 }
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use std::{fmt, marker::PhantomData};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{DenseStorage, DenseStorageIndex};
+
+    // Serialized as `(index, generation)`
+    impl<T> Serialize for DenseStorageIndex<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            (self.0, self.1).serialize(serializer)
+        }
+    }
+
+    impl<'de, T> Deserialize<'de> for DenseStorageIndex<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let (index, generation) = <(usize, u32)>::deserialize(deserializer)?;
+            Ok(DenseStorageIndex(index, generation, PhantomData))
+        }
+    }
+
+    /// The serialized form of `DenseStorage`, checked before it's converted.
+    #[derive(Deserialize)]
+    pub(super) struct SerializedDenseStorage<T> {
+        storage: Vec<(u32, Option<T>)>,
+        recycled_indices: Vec<usize>,
+    }
+
+    /// The recycled indices of a deserialized `DenseStorage` don't match its empty slots.
+    #[derive(Debug)]
+    pub(super) struct InvalidFreeList;
+
+    impl fmt::Display for InvalidFreeList {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "the recycled indices don't match the empty slots")
+        }
+    }
+
+    impl<T> TryFrom<SerializedDenseStorage<T>> for DenseStorage<T> {
+        type Error = InvalidFreeList;
+
+        fn try_from(serialized: SerializedDenseStorage<T>) -> Result<Self, Self::Error> {
+            let mut recycled = vec![false; serialized.storage.len()];
+            for &i in &serialized.recycled_indices {
+                match recycled.get_mut(i) {
+                    Some(recycled) if !*recycled => *recycled = true,
+                    _ => return Err(InvalidFreeList),
+                }
+            }
+            let valid = serialized
+                .storage
+                .iter()
+                .zip(&recycled)
+                .all(|((_, value), &recycled)| value.is_none() == recycled);
+            if !valid {
+                return Err(InvalidFreeList);
+            }
+
+            Ok(Self {
+                storage: serialized.storage,
+                recycled_indices: serialized.recycled_indices,
+                strong_counts: Vec::new(),
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use serde::de::DeserializeOwned;
+
+        use super::*;
+
+        fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+            ron::from_str(&ron::to_string(value).unwrap()).unwrap()
+        }
+
+        #[test]
+        fn indices_stay_valid_after_a_round_trip() {
+            let mut storage = DenseStorage::new();
+            let a = storage.push("a".to_string());
+            let b = storage.push("b".to_string());
+            let c = storage.push("c".to_string());
+            storage.remove(b);
+            let d = storage.push("d".to_string());
+            storage.remove(c);
+
+            let (mut storage, [a, b, c, d]): (
+                DenseStorage<String>,
+                [DenseStorageIndex<String>; 4],
+            ) = round_trip(&(storage, [a, b, c, d]));
+
+            assert_eq!(storage.len(), 2);
+            assert_eq!(storage.get(a).map(String::as_str), Some("a"));
+            assert_eq!(storage.get(b), None);
+            assert_eq!(storage.get(c), None);
+            assert_eq!(storage.get(d).map(String::as_str), Some("d"));
+
+            // The free list survives, so the removed slot is reused with a new generation
+            let e = storage.push("e".to_string());
+            assert_eq!(e, DenseStorageIndex::new(c.0, c.1 + 1));
+            assert_eq!(storage.get(c), None);
+        }
+
+        #[test]
+        fn deserialized_values_arent_reference_counted() {
+            let mut storage = DenseStorage::new();
+            let handle = storage.push_strong(1);
+
+            let mut storage: DenseStorage<i32> = round_trip(&storage);
+            let index = handle.index();
+            drop(handle);
+
+            assert_eq!(storage.strong_count(index), None);
+            assert!(storage.upgrade(index).is_none());
+            assert!(storage.remove_unused().is_empty());
+            assert_eq!(storage.get(index), Some(&1));
+
+            // Values pushed after deserializing are reference-counted again
+            let handle = storage.push_strong(2);
+            let new_index = handle.index();
+            drop(handle);
+            assert_eq!(storage.remove_unused(), vec![(new_index, 2)]);
+        }
+
+        #[test]
+        fn corrupted_free_lists_are_rejected() {
+            let corrupted = [
+                // A recycled index is listed twice
+                "(storage: [(0, Some(1)), (1, None)], recycled_indices: [1, 1])",
+                // A recycled index points to a value
+                "(storage: [(0, Some(1)), (1, None)], recycled_indices: [0])",
+                // A recycled index is past the end of the storage
+                "(storage: [(0, Some(1)), (1, None)], recycled_indices: [1, 2])",
+                // An empty slot isn't recycled
+                "(storage: [(0, Some(1)), (1, None), (3, None)], recycled_indices: [1])",
+            ];
+
+            for serialized in corrupted {
+                let error = ron::from_str::<DenseStorage<i32>>(serialized).unwrap_err();
+                assert!(
+                    error.to_string().contains(&InvalidFreeList.to_string()),
+                    "{serialized}: {error}"
+                );
+            }
+        }
+
+        #[test]
+        fn valid_storage_is_accepted() {
+            let storage: DenseStorage<i32> = ron::from_str(
+                "(storage: [(0, Some(1)), (4, None), (2, Some(3))], recycled_indices: [1])",
+            )
+            .unwrap();
+
+            assert_eq!(storage.len(), 2);
+            assert_eq!(storage.get(DenseStorageIndex::new(2, 2)), Some(&3));
+            assert_eq!(storage.get(DenseStorageIndex::new(2, 1)), None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_mut_and_index_mut_modify_values() {
+        let mut storage = DenseStorage::new();
+        let a = storage.push(1);
+        let b = storage.push(2);
+
+        *storage.get_mut(a).unwrap() += 10;
+        storage[b] += 20;
+
+        assert_eq!(storage.get(a), Some(&11));
+        assert_eq!(storage[b], 22);
+    }
+
+    #[test]
+    fn contains_len_and_is_empty_track_values() {
+        let mut storage = DenseStorage::new();
+        assert!(storage.is_empty());
+
+        let a = storage.push("a");
+        let b = storage.push("b");
+        assert!(storage.contains(a) && storage.contains(b));
+        assert_eq!(storage.len(), 2);
+        assert!(!storage.is_empty());
+
+        storage.remove(a);
+        assert!(!storage.contains(a));
+        assert_eq!(storage.len(), 1);
+    }
+
+    #[test]
+    fn stale_indices_fail_after_remove() {
+        let mut storage = DenseStorage::new();
+        let a = storage.push(1);
+
+        assert_eq!(storage.remove(a), Some(1));
+        assert_eq!(storage.get(a), None);
+        assert_eq!(storage.get_mut(a), None);
+        assert_eq!(storage.remove(a), None);
+    }
+
+    #[test]
+    fn recycled_slots_bump_the_generation() {
+        let mut storage = DenseStorage::new();
+        let a = storage.push(1);
+        storage.remove(a);
+        let b = storage.push(2);
+
+        assert_eq!(b.0, a.0);
+        assert_eq!(b.1, a.1 + 1);
+        assert_eq!(storage.get(a), None);
+        assert_eq!(storage.get(b), Some(&2));
+    }
+
+    #[test]
+    fn len_stays_correct_while_recycling() {
+        let mut storage = DenseStorage::new();
+        let indices: Vec<_> = (0..4).map(|i| storage.push(i)).collect();
+
+        storage.remove(indices[1]);
+        storage.remove(indices[2]);
+        assert_eq!(storage.len(), 2);
+
+        storage.push(10);
+        assert_eq!(storage.len(), 3);
+        storage.push(11);
+        storage.push(12);
+        assert_eq!(storage.len(), 5);
+        assert_eq!(storage.iter().count(), 5);
+    }
+
+    #[test]
+    fn retain_removes_rejected_values() {
+        let mut storage = DenseStorage::new();
+        let indices: Vec<_> = (0..6).map(|i| storage.push(i)).collect();
+
+        storage.retain(|_, value| {
+            *value *= 10;
+            *value % 20 == 0
+        });
+
+        assert_eq!(storage.len(), 3);
+        for (i, &index) in indices.iter().enumerate() {
+            if i % 2 == 0 {
+                assert_eq!(storage.get(index), Some(&(i * 10)));
+            } else {
+                assert!(!storage.contains(index));
+            }
+        }
+    }
+
+    #[test]
+    fn drain_returns_every_value_and_invalidates_indices() {
+        let mut storage = DenseStorage::new();
+        let a = storage.push('a');
+        let b = storage.push('b');
+        storage.remove(a);
+        let c = storage.push('c');
+
+        let drained: Vec<_> = storage.drain().collect();
+
+        assert_eq!(drained, [(c, 'c'), (b, 'b')]);
+        assert!(storage.is_empty());
+        assert!(!storage.contains(b) && !storage.contains(c));
+    }
+
+    #[test]
+    fn clear_invalidates_indices() {
+        let mut storage = DenseStorage::new();
+        let a = storage.push(1);
+        let b = storage.push(2);
+
+        storage.clear();
+        assert!(storage.is_empty());
+        assert!(!storage.contains(a) && !storage.contains(b));
+
+        let c = storage.push(3);
+        assert_ne!(c, a);
+        assert_ne!(c, b);
+        assert_eq!(storage.get(a), None);
+        assert_eq!(storage.get(b), None);
+        assert_eq!(storage.len(), 1);
+    }
+
+    #[test]
+    fn reserve_and_with_capacity_allocate_space() {
+        let storage = DenseStorage::<u32>::with_capacity(8);
+        assert!(storage.capacity() >= 8);
+        assert!(storage.is_empty());
+
+        let mut storage = DenseStorage::new();
+        storage.reserve(16);
+        let capacity = storage.capacity();
+        assert!(capacity >= 16);
+        for i in 0..16 {
+            storage.push(i);
+        }
+        assert_eq!(storage.capacity(), capacity);
+    }
+
+    #[test]
+    fn reserve_counts_recycled_slots() {
+        let mut storage = DenseStorage::new();
+        let indices: Vec<_> = (0..4).map(|i| storage.push(i)).collect();
+        for index in indices {
+            storage.remove(index);
+        }
+
+        let capacity = storage.capacity();
+        storage.reserve(4);
+        assert_eq!(storage.capacity(), capacity);
+    }
+
+    #[test]
+    #[should_panic(expected = "no value exists")]
+    fn index_panics_on_stale_indices() {
+        let mut storage = DenseStorage::new();
+        let a = storage.push(1);
+        storage.remove(a);
+
+        let _ = storage[a];
+    }
+
+    #[test]
+    #[should_panic(expected = "no value exists")]
+    fn index_mut_panics_on_stale_indices() {
+        let mut storage = DenseStorage::new();
+        let a = storage.push(1);
+        storage.clear();
+
+        storage[a] = 2;
+    }
+}
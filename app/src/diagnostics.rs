@@ -0,0 +1,158 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy_ecs::prelude::*;
+
+pub use render::render_pipeline::RenderStats;
+
+/// The weight of the latest frame in the smoothed frame time.
+const SMOOTHING: f32 = 0.1;
+
+/// Frame time statistics over the rolling window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameTimeStats {
+    pub min: Duration,
+    pub average: Duration,
+    pub max: Duration,
+    /// The frame time 99% of frames were faster than.
+    pub p99: Duration,
+}
+
+/// Tracks frame times and render stats. (Optionally printed periodically)
+#[derive(Resource)]
+pub struct Diagnostics {
+    /// The frame times of the rolling window, oldest first.
+    frame_times: VecDeque<Duration>,
+    window_size: usize,
+    /// The exponential moving average of the frame time in seconds.
+    smoothed_frame_time: f32,
+    render_stats: RenderStats,
+    pub(crate) culled_count: u32,
+    log_interval: Option<Duration>,
+    since_log: Duration,
+}
+
+impl Diagnostics {
+    /// Creates diagnostics with a rolling window of `window_size` frames.
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            frame_times: VecDeque::with_capacity(window_size),
+            window_size: window_size.max(1),
+            smoothed_frame_time: 0.0,
+            render_stats: RenderStats::default(),
+            culled_count: 0,
+            log_interval: None,
+            since_log: Duration::ZERO,
+        }
+    }
+
+    /// Sets the interval to print the diagnostics at.
+    pub fn with_log_interval(mut self, log_interval: Duration) -> Self {
+        self.log_interval = Some(log_interval);
+        self
+    }
+
+    /// Sets the interval to print the diagnostics at or `None` to stop printing.
+    pub fn set_log_interval(&mut self, log_interval: Option<Duration>) {
+        self.log_interval = log_interval;
+        self.since_log = Duration::ZERO;
+    }
+
+    /// Sets the number of frames in the rolling window. (Drops the oldest frames if smaller)
+    pub fn set_window_size(&mut self, window_size: usize) {
+        self.window_size = window_size.max(1);
+        while self.frame_times.len() > self.window_size {
+            self.frame_times.pop_front();
+        }
+    }
+
+    /// Gets the smoothed frames per second.
+    pub fn get_fps(&self) -> f32 {
+        if self.smoothed_frame_time > 0.0 {
+            1.0 / self.smoothed_frame_time
+        } else {
+            0.0
+        }
+    }
+
+    /// Gets the frame times of the rolling window, oldest first. (For drawing a histogram)
+    pub fn get_frame_times(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.frame_times.iter().copied()
+    }
+
+    /// Gets the frame time statistics of the rolling window.
+    pub fn get_frame_time_stats(&self) -> FrameTimeStats {
+        if self.frame_times.is_empty() {
+            return FrameTimeStats::default();
+        }
+
+        let mut frame_times: Vec<_> = self.frame_times.iter().copied().collect();
+        frame_times.sort_unstable();
+        let p99_index = ((frame_times.len() - 1) as f32 * 0.99).round() as usize;
+
+        FrameTimeStats {
+            min: frame_times[0],
+            average: frame_times.iter().sum::<Duration>() / frame_times.len() as u32,
+            max: frame_times[frame_times.len() - 1],
+            p99: frame_times[p99_index],
+        }
+    }
+
+    /// Gets the render stats of the last frame.
+    pub fn get_render_stats(&self) -> RenderStats {
+        self.render_stats
+    }
+
+    /// Gets the number of entities that weren't drawn because they're hidden.
+    pub fn get_culled_count(&self) -> u32 {
+        self.culled_count
+    }
+
+    /// Records a rendered frame and prints the diagnostics if the log interval passed.
+    pub(crate) fn record_frame(&mut self, frame_time: Duration, render_stats: RenderStats) {
+        if self.frame_times.len() == self.window_size {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+
+        self.smoothed_frame_time = if self.smoothed_frame_time > 0.0 {
+            self.smoothed_frame_time * (1.0 - SMOOTHING) + frame_time.as_secs_f32() * SMOOTHING
+        } else {
+            frame_time.as_secs_f32()
+        };
+        self.render_stats = render_stats;
+
+        let Some(log_interval) = self.log_interval else {
+            return;
+        };
+        self.since_log += frame_time;
+        if self.since_log >= log_interval {
+            self.since_log = Duration::ZERO;
+            self.log();
+        }
+    }
+
+    fn log(&self) {
+        let frame_time_stats = self.get_frame_time_stats();
+        let render_stats = self.render_stats;
+        println!(
+            "{:.1} fps | frame time min {:.2?} avg {:.2?} max {:.2?} p99 {:.2?} | {} instances, \
+             {} culled, {} draw calls, {} textures, {} buffer reallocations",
+            self.get_fps(),
+            frame_time_stats.min,
+            frame_time_stats.average,
+            frame_time_stats.max,
+            frame_time_stats.p99,
+            render_stats.instance_count,
+            self.culled_count,
+            render_stats.draw_calls,
+            render_stats.texture_count,
+            render_stats.buffer_reallocations,
+        );
+    }
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self::new(120)
+    }
+}
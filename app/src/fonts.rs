@@ -0,0 +1,248 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use bevy_ecs::prelude::*;
+
+use crate::prelude::{DenseStorage, DenseStorageIndex, Texture};
+
+/// A bitmap font parsed from an AngelCode BMFont text file (.fnt).
+#[derive(Clone)]
+pub struct BitmapFont {
+    /// The distance between lines in pixels.
+    pub line_height: u32,
+    /// The distance from the top of a line to the baseline in pixels.
+    pub base: u32,
+    /// The size of the page textures in pixels.
+    pub scale: (u32, u32),
+    /// The page file names in the order of their ids.
+    pub page_files: Vec<String>,
+    /// The loaded page textures in the order of their ids.
+    pub page_textures: Vec<DenseStorageIndex<Texture>>,
+    /// The glyphs by character.
+    pub glyphs: HashMap<char, Glyph>,
+    /// The kerning amounts in pixels by character pair.
+    pub kernings: HashMap<(char, char), i32>,
+}
+
+impl BitmapFont {
+    /// Parses a BMFont text file. The page textures need to be loaded and set with
+    /// `with_page_textures()` before the font can be drawn.
+    pub fn parse(source: &str) -> Result<Self, BitmapFontError> {
+        let mut font = Self {
+            line_height: 0,
+            base: 0,
+            scale: (0, 0),
+            page_files: Vec::new(),
+            page_textures: Vec::new(),
+            glyphs: HashMap::new(),
+            kernings: HashMap::new(),
+        };
+        // The page count from the `common` line, page ids need to be below it
+        let mut page_count = None;
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let mut tokens = Tokens::new(line);
+            let Some(tag) = tokens.next() else {
+                continue;
+            };
+            let attributes: HashMap<&str, &str> = tokens
+                .map(|token| token.split_once('=').unwrap_or((token, "")))
+                .map(|(key, value)| (key, value.trim_matches('"')))
+                .collect();
+            let get = |key: &'static str| -> Result<i32, BitmapFontError> {
+                let value = attributes
+                    .get(key)
+                    .ok_or(BitmapFontError::MissingAttribute { line_number, key })?;
+                value
+                    .parse()
+                    .map_err(|_| BitmapFontError::InvalidAttribute { line_number, key })
+            };
+            let get_unsigned = |key: &'static str| -> Result<u32, BitmapFontError> {
+                u32::try_from(get(key)?)
+                    .map_err(|_| BitmapFontError::InvalidAttribute { line_number, key })
+            };
+            let get_page = |key: &'static str| -> Result<usize, BitmapFontError> {
+                let page_count = page_count.ok_or(BitmapFontError::MissingCommon)?;
+                usize::try_from(get(key)?)
+                    .ok()
+                    .filter(|&page| page < page_count)
+                    .ok_or(BitmapFontError::InvalidAttribute { line_number, key })
+            };
+
+            match tag {
+                "common" => {
+                    font.line_height = get_unsigned("lineHeight")?;
+                    font.base = get_unsigned("base")?;
+                    font.scale = (get_unsigned("scaleW")?, get_unsigned("scaleH")?);
+                    // BMFont stores the page count in 16 bits, which also bounds `page_files`
+                    page_count = Some(
+                        u16::try_from(get("pages")?)
+                            .map_err(|_| BitmapFontError::InvalidAttribute {
+                                line_number,
+                                key: "pages",
+                            })?
+                            .into(),
+                    );
+                }
+                "page" => {
+                    let id = get_page("id")?;
+                    let file = attributes
+                        .get("file")
+                        .ok_or(BitmapFontError::MissingAttribute {
+                            line_number,
+                            key: "file",
+                        })?;
+
+                    if font.page_files.len() <= id {
+                        font.page_files.resize(id + 1, String::new());
+                    }
+                    font.page_files[id] = file.to_string();
+                }
+                "char" => {
+                    let character = char::from_u32(get_unsigned("id")?).ok_or(
+                        BitmapFontError::InvalidAttribute {
+                            line_number,
+                            key: "id",
+                        },
+                    )?;
+
+                    font.glyphs.insert(
+                        character,
+                        Glyph {
+                            position: (get_unsigned("x")?, get_unsigned("y")?),
+                            size: (get_unsigned("width")?, get_unsigned("height")?),
+                            offset: (get("xoffset")?, get("yoffset")?),
+                            advance: get("xadvance")?,
+                            page: get_page("page")?,
+                        },
+                    );
+                }
+                "kerning" => {
+                    let first = char::from_u32(get_unsigned("first")?);
+                    let second = char::from_u32(get_unsigned("second")?);
+
+                    if let (Some(first), Some(second)) = (first, second) {
+                        font.kernings.insert((first, second), get("amount")?);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if page_count.is_none() {
+            return Err(BitmapFontError::MissingCommon);
+        }
+
+        Ok(font)
+    }
+
+    /// Sets the loaded page textures in the order of their ids.
+    pub fn with_page_textures(mut self, page_textures: Vec<DenseStorageIndex<Texture>>) -> Self {
+        self.page_textures = page_textures;
+        self
+    }
+}
+
+/// A character in a `BitmapFont`.
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    /// The top left corner of the glyph in the page texture in pixels.
+    pub position: (u32, u32),
+    /// The size of the glyph in pixels.
+    pub size: (u32, u32),
+    /// The offset from the pen position to the top left corner of the glyph in pixels.
+    pub offset: (i32, i32),
+    /// How far to move the pen after drawing the glyph in pixels.
+    pub advance: i32,
+    /// The page the glyph is on.
+    pub page: usize,
+}
+
+/// An error that occurs when parsing a BMFont text file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BitmapFontError {
+    /// The file has no `common` line or a page is used before it.
+    MissingCommon,
+    /// A required attribute is missing.
+    MissingAttribute {
+        line_number: usize,
+        key: &'static str,
+    },
+    /// An attribute isn't a valid number.
+    InvalidAttribute {
+        line_number: usize,
+        key: &'static str,
+    },
+}
+
+impl fmt::Display for BitmapFontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitmapFontError::MissingCommon => write!(f, "missing common line"),
+            BitmapFontError::MissingAttribute { line_number, key } => {
+                write!(f, "missing attribute `{key}` on line {line_number}")
+            }
+            BitmapFontError::InvalidAttribute { line_number, key } => {
+                write!(f, "invalid attribute `{key}` on line {line_number}")
+            }
+        }
+    }
+}
+
+impl Error for BitmapFontError {}
+
+/// Splits a BMFont line by whitespace, keeping quoted values together.
+struct Tokens<'a> {
+    line: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(line: &'a str) -> Self {
+        Self { line }
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.line = self.line.trim_start();
+        if self.line.is_empty() {
+            return None;
+        }
+
+        let mut in_quotes = false;
+        let end = self
+            .line
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                }
+                c.is_whitespace() && !in_quotes
+            })
+            .map_or(self.line.len(), |(i, _)| i);
+
+        let (token, rest) = self.line.split_at(end);
+        self.line = rest;
+        Some(token)
+    }
+}
+
+/// Holds the bitmap fonts that `Text` can use.
+#[derive(Default, Resource)]
+pub struct Fonts {
+    pub(crate) fonts: DenseStorage<BitmapFont>,
+    pub(crate) changed: bool,
+}
+
+impl Fonts {
+    pub fn get_fonts(&self) -> &DenseStorage<BitmapFont> {
+        &self.fonts
+    }
+
+    pub fn get_fonts_mut(&mut self) -> &mut DenseStorage<BitmapFont> {
+        self.changed = true;
+        &mut self.fonts
+    }
+}
@@ -0,0 +1,73 @@
+use std::f32::consts::TAU;
+
+use bevy_ecs::{prelude::*, system::SystemParam};
+use render::{glam::Vec2, prelude::LineVertex, wgpu};
+
+/// The amount of line segments used to draw a circle.
+const CIRCLE_SEGMENTS: u32 = 32;
+
+/// Holds the gizmo lines drawn this frame.
+#[derive(Default, Resource)]
+pub struct GizmoBuffer {
+    pub(crate) lines: Vec<LineVertex>,
+}
+
+/// Draws debug lines and shapes in world space for the current frame. (Gizmos drawn in
+/// `PostUpdate` show up on the next frame)
+#[derive(SystemParam)]
+pub struct Gizmos<'w> {
+    buffer: ResMut<'w, GizmoBuffer>,
+}
+
+impl Gizmos<'_> {
+    /// Draws a line from `start` to `end`.
+    pub fn line(&mut self, start: Vec2, end: Vec2, color: wgpu::Color) {
+        let color = [
+            color.r as f32,
+            color.g as f32,
+            color.b as f32,
+            color.a as f32,
+        ];
+
+        self.buffer.lines.extend([
+            LineVertex::new(start.extend(0.0), color),
+            LineVertex::new(end.extend(0.0), color),
+        ]);
+    }
+
+    /// Draws the outline of a rectangle.
+    pub fn rect(&mut self, center: Vec2, size: Vec2, color: wgpu::Color) {
+        let half_size = size * 0.5;
+        let corners = [
+            center + Vec2::new(-half_size.x, -half_size.y),
+            center + Vec2::new(half_size.x, -half_size.y),
+            center + Vec2::new(half_size.x, half_size.y),
+            center + Vec2::new(-half_size.x, half_size.y),
+        ];
+
+        for i in 0..corners.len() {
+            self.line(corners[i], corners[(i + 1) % corners.len()], color);
+        }
+    }
+
+    /// Draws the outline of a circle.
+    pub fn circle(&mut self, center: Vec2, radius: f32, color: wgpu::Color) {
+        let point =
+            |i: u32| center + Vec2::from_angle(i as f32 / CIRCLE_SEGMENTS as f32 * TAU) * radius;
+
+        for i in 0..CIRCLE_SEGMENTS {
+            self.line(point(i), point(i + 1), color);
+        }
+    }
+
+    /// Draws an arrow from `start` pointing at `end`.
+    pub fn arrow(&mut self, start: Vec2, end: Vec2, color: wgpu::Color) {
+        self.line(start, end, color);
+
+        let head_length = start.distance(end) * 0.2;
+        let back = (start - end).normalize_or_zero() * head_length;
+        for angle in [-0.5, 0.5] {
+            self.line(end, end + Vec2::from_angle(angle).rotate(back), color);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+
+pub use render::gpu_timings::PassTiming;
+
+/// Holds how long the GPU spent on each render pass of a recent frame. (Read back a few frames
+/// late, empty if the adapter doesn't support timestamp queries)
+#[derive(Default, Resource)]
+pub struct GpuTimings {
+    pub(crate) passes: Vec<PassTiming>,
+    pub(crate) supported: bool,
+}
+
+impl GpuTimings {
+    pub fn get_passes(&self) -> &[PassTiming] {
+        &self.passes
+    }
+
+    /// Gets the duration of the pass with the label. (`"camera 0"`, `"post process 0"`, `"blit"`)
+    pub fn get_pass(&self, label: &str) -> Option<Duration> {
+        self.passes
+            .iter()
+            .find(|pass| pass.label == label)
+            .map(|pass| pass.duration)
+    }
+
+    /// Gets the summed duration of all passes.
+    pub fn get_total(&self) -> Duration {
+        self.passes.iter().map(|pass| pass.duration).sum()
+    }
+
+    /// Checks if the adapter supports timing render passes.
+    pub fn is_supported(&self) -> bool {
+        self.supported
+    }
+}
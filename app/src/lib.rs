@@ -1,18 +1,41 @@
 pub mod app;
 pub mod camera;
 pub mod dense_storage;
+pub mod diagnostics;
+pub mod fonts;
+pub mod gizmos;
+pub mod gpu_timings;
+pub mod lighting;
 pub mod main_schedules;
 pub mod material;
+pub mod nine_slice;
+pub mod particles;
+pub mod post_processes;
+pub mod recording;
+#[cfg(feature = "scene")]
+pub mod scene;
+pub mod screenshot;
+pub mod shaders;
+pub mod sprite_animation;
+pub mod text;
+pub mod texture_containers;
+pub mod texture_loader;
 pub mod textures;
+pub mod tilemap;
 pub mod visibility;
 
+mod texture_decompression;
 mod update_render_state;
 
 pub use {bevy_ecs, bevy_transform, render};
 
 pub mod prelude {
+    #[cfg(feature = "scene")]
+    pub use crate::scene::*;
     pub use crate::{
-        app::*, camera::*, dense_storage::*, main_schedules::*, material::*, textures::*,
-        visibility::*,
+        app::*, camera::*, dense_storage::*, diagnostics::*, fonts::*, gizmos::*, gpu_timings::*,
+        lighting::*, main_schedules::*, material::*, nine_slice::*, particles::*,
+        post_processes::*, recording::*, screenshot::*, shaders::*, sprite_animation::*, text::*,
+        texture_containers::*, texture_loader::*, textures::*, tilemap::*, visibility::*,
     };
 }
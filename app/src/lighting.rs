@@ -0,0 +1,124 @@
+use bevy_ecs::prelude::*;
+use bevy_transform::components::Transform;
+use render::{glam::Vec2, prelude::PointLight, wgpu};
+
+/// Lights the sprites drawn by the world `Camera` within its radius. Sprites with a normal map on
+/// their `Material` are shaded by the light direction, other sprites only by the distance.
+#[derive(Clone, Copy, Component)]
+#[require(Transform)]
+pub struct PointLight2d {
+    /// The linear light color.
+    pub color: wgpu::Color,
+    pub intensity: f32,
+    /// The distance where the light fades out completely in world units.
+    pub radius: f32,
+    /// How high above the sprites the light is, which changes how normal maps are shaded.
+    pub height: f32,
+}
+
+impl PointLight2d {
+    /// Creates a new `PointLight2d` a quarter of its radius above the sprites.
+    pub fn new(color: wgpu::Color, intensity: f32, radius: f32) -> Self {
+        Self {
+            color,
+            intensity,
+            radius,
+            height: radius * 0.25,
+        }
+    }
+
+    /// Sets how high above the sprites the light is.
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+}
+
+/// The light that reaches every sprite drawn by the world `Camera`. (Currently only one ambient
+/// light is supported)
+///
+/// Sprites are drawn unlit until a `PointLight2d` or `AmbientLight2d` is spawned, after which the
+/// ambient light is black unless set.
+#[derive(Clone, Copy, Component)]
+pub struct AmbientLight2d {
+    /// The linear light color.
+    pub color: wgpu::Color,
+    pub intensity: f32,
+}
+
+impl AmbientLight2d {
+    pub fn new(color: wgpu::Color, intensity: f32) -> Self {
+        Self { color, intensity }
+    }
+}
+
+/// Configures the 2D lighting.
+#[derive(Clone, Copy, Resource)]
+pub struct LightSettings2d {
+    /// The maximum amount of point lights a pixel is shaded with. (The lights closest to the
+    /// camera are kept)
+    pub max_point_lights: usize,
+}
+
+impl Default for LightSettings2d {
+    fn default() -> Self {
+        Self {
+            max_point_lights: 64,
+        }
+    }
+}
+
+/// Gets the ambient light and the closest point lights to the camera or `None` if there are no
+/// lights.
+pub(crate) fn extract_lights(
+    world: &mut World,
+    camera_position: Vec2,
+) -> Option<([f32; 4], Vec<PointLight>)> {
+    let ambient_light = world.query::<&AmbientLight2d>().iter(world).next().copied();
+
+    let mut point_lights: Vec<_> = world
+        .query::<(&Transform, &PointLight2d)>()
+        .iter(world)
+        .map(|(transform, point_light)| (transform.translation.truncate(), *point_light))
+        .collect();
+
+    if ambient_light.is_none() && point_lights.is_empty() {
+        return None;
+    }
+
+    let max_point_lights = world.resource::<LightSettings2d>().max_point_lights;
+    if point_lights.len() > max_point_lights {
+        point_lights.sort_by(|a, b| {
+            let a = a.0.distance_squared(camera_position);
+            let b = b.0.distance_squared(camera_position);
+            a.total_cmp(&b)
+        });
+        point_lights.truncate(max_point_lights);
+    }
+
+    let ambient_light = ambient_light.map_or([0.0; 4], |ambient_light| {
+        premultiply(ambient_light.color, ambient_light.intensity)
+    });
+    let point_lights = point_lights
+        .into_iter()
+        .map(|(position, point_light)| {
+            let [r, g, b, _] = premultiply(point_light.color, point_light.intensity);
+            PointLight::new(
+                position.extend(point_light.height),
+                point_light.radius,
+                [r, g, b],
+            )
+        })
+        .collect();
+
+    Some((ambient_light, point_lights))
+}
+
+fn premultiply(color: wgpu::Color, intensity: f32) -> [f32; 4] {
+    [
+        color.r as f32 * intensity,
+        color.g as f32 * intensity,
+        color.b as f32 * intensity,
+        color.a as f32,
+    ]
+}
@@ -1,15 +1,47 @@
 use bevy_ecs::component::Component;
 
-use crate::prelude::{DenseStorageIndex, Sampler, Texture};
+use crate::prelude::{DenseStorageIndex, Handle, Sampler, Shader, Texture};
 
 #[derive(Component)]
 pub struct Material {
-    pub texture: DenseStorageIndex<Texture>,
-    pub sampler: DenseStorageIndex<Sampler>,
+    /// The texture to draw. (A strong handle keeps the texture loaded)
+    pub texture: Handle<Texture>,
+    pub sampler: Handle<Sampler>,
+    /// The region of the texture to draw as `[u, v, width, height]` in texture coordinates. (V
+    /// points down from the top row)
+    pub uv_rect: [f32; 4],
+    /// The custom shader to draw with or `None` to use the main shader.
+    pub shader: Option<DenseStorageIndex<Shader>>,
+    /// The tangent-space normal map used by `PointLight2d` lighting. (Same layout as `texture`)
+    pub normal_map: Option<Handle<Texture>>,
 }
 
 impl Material {
-    pub fn new(texture: DenseStorageIndex<Texture>, sampler: DenseStorageIndex<Sampler>) -> Self {
-        Self { texture, sampler }
+    pub fn new(texture: impl Into<Handle<Texture>>, sampler: impl Into<Handle<Sampler>>) -> Self {
+        Self {
+            texture: texture.into(),
+            sampler: sampler.into(),
+            uv_rect: [0.0, 0.0, 1.0, 1.0],
+            shader: None,
+            normal_map: None,
+        }
+    }
+
+    /// Sets the region of the texture to draw as `[u, v, width, height]` in texture coordinates.
+    pub fn with_uv_rect(mut self, uv_rect: [f32; 4]) -> Self {
+        self.uv_rect = uv_rect;
+        self
+    }
+
+    /// Sets the custom shader.
+    pub fn with_shader(mut self, shader: DenseStorageIndex<Shader>) -> Self {
+        self.shader = Some(shader);
+        self
+    }
+
+    /// Sets the normal map.
+    pub fn with_normal_map(mut self, normal_map: impl Into<Handle<Texture>>) -> Self {
+        self.normal_map = Some(normal_map.into());
+        self
     }
 }
@@ -0,0 +1,244 @@
+use bevy_ecs::prelude::*;
+use bevy_transform::components::Transform;
+use render::glam::{Mat4, Quat, Vec3};
+
+/// The most tiles per axis before the middle is stretched instead.
+const MAX_TILE_COUNT: f32 = 64.0;
+
+/// Draws the entity's `Material` texture as a nine-slice sprite. The corners keep their size, the
+/// edges and center are stretched or tiled to fill the `Transform` scale.
+#[derive(Clone, Copy, Component)]
+pub struct NineSlice {
+    /// The left border width in texture pixels.
+    pub left: u32,
+    /// The right border width in texture pixels.
+    pub right: u32,
+    /// The top border height in texture pixels.
+    pub top: u32,
+    /// The bottom border height in texture pixels.
+    pub bottom: u32,
+    /// The size of a texture pixel in world units.
+    pub pixel_scale: f32,
+    /// How the edges and center fill the space between the corners.
+    pub mode: NineSliceMode,
+}
+
+impl NineSlice {
+    /// Creates a new `NineSlice` with the same border size on every side.
+    pub fn new(border: u32, pixel_scale: f32) -> Self {
+        Self {
+            left: border,
+            right: border,
+            top: border,
+            bottom: border,
+            pixel_scale,
+            mode: NineSliceMode::Stretch,
+        }
+    }
+
+    /// Sets how the edges and center fill the space between the corners.
+    pub fn with_mode(mut self, mode: NineSliceMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Gets the transform and uv rect of every quad needed to draw the nine-slice.
+    pub(crate) fn quads(
+        &self,
+        transform: &Transform,
+        texture_size: (u32, u32),
+    ) -> Vec<(Mat4, [f32; 4])> {
+        let columns = self.segments(
+            transform.scale.x,
+            texture_size.0,
+            self.left,
+            self.right,
+            false,
+        );
+        let rows = self.segments(
+            transform.scale.y,
+            texture_size.1,
+            self.top,
+            self.bottom,
+            true,
+        );
+
+        // The quads are scaled individually so the entity scale is only used for the size
+        let matrix = Transform {
+            scale: Vec3::new(
+                transform.scale.x.signum(),
+                transform.scale.y.signum(),
+                transform.scale.z,
+            ),
+            ..*transform
+        }
+        .compute_matrix();
+
+        rows.iter()
+            .flat_map(|row| columns.iter().map(move |column| (column, row)))
+            .map(|(column, row)| {
+                let quad = Mat4::from_scale_rotation_translation(
+                    Vec3::new(column.size, row.size, 1.0),
+                    Quat::IDENTITY,
+                    Vec3::new(
+                        column.start + column.size * 0.5,
+                        row.start + row.size * 0.5,
+                        0.0,
+                    ),
+                );
+
+                (
+                    matrix * quad,
+                    [column.uv_start, row.uv_start, column.uv_size, row.uv_size],
+                )
+            })
+            .collect()
+    }
+
+    /// Splits one axis into the segments of the start border, middle and end border. The segment
+    /// positions are centered on 0 and go in the positive direction unless `flip` is set (the
+    /// texture's v axis points down).
+    fn segments(
+        &self,
+        size: f32,
+        texture_size: u32,
+        start_border: u32,
+        end_border: u32,
+        flip: bool,
+    ) -> Vec<Segment> {
+        // Nothing sensible can be drawn at an infinite or NaN scale
+        if !size.is_finite() {
+            return Vec::new();
+        }
+
+        let texture_size = texture_size.max(1) as f32;
+        let uv_start_border = start_border as f32 / texture_size;
+        let uv_end_border = end_border as f32 / texture_size;
+        let uv_middle = (1.0 - uv_start_border - uv_end_border).max(0.0);
+
+        // Shrink the borders if they don't fit
+        let mut start_size = start_border as f32 * self.pixel_scale;
+        let mut end_size = end_border as f32 * self.pixel_scale;
+        let border_size = start_size + end_size;
+        if border_size > size.abs() {
+            let shrink = size.abs() / border_size;
+            start_size *= shrink;
+            end_size *= shrink;
+        }
+        let middle_size = size.abs() - start_size - end_size;
+
+        let mut segments = vec![Segment {
+            start: 0.0,
+            size: start_size,
+            uv_start: 0.0,
+            uv_size: uv_start_border,
+        }];
+
+        // Tiles that are too small to count are stretched instead
+        let tile_size = uv_middle * texture_size * self.pixel_scale;
+        let tile_count = (middle_size / tile_size).ceil();
+        match self.mode {
+            NineSliceMode::Tile if tile_size > 0.0 && tile_count <= MAX_TILE_COUNT => {
+                for i in 0..tile_count as u32 {
+                    let start = start_size + i as f32 * tile_size;
+                    let size = tile_size.min(start_size + middle_size - start);
+                    segments.push(Segment {
+                        start,
+                        size,
+                        uv_start: uv_start_border,
+                        uv_size: uv_middle * size / tile_size,
+                    });
+                }
+            }
+            _ => segments.push(Segment {
+                start: start_size,
+                size: middle_size,
+                uv_start: uv_start_border,
+                uv_size: uv_middle,
+            }),
+        }
+
+        segments.push(Segment {
+            start: start_size + middle_size,
+            size: end_size,
+            uv_start: 1.0 - uv_end_border,
+            uv_size: uv_end_border,
+        });
+
+        segments.retain(|segment| segment.size > 0.0);
+        for segment in &mut segments {
+            segment.start -= size.abs() * 0.5;
+            if flip {
+                segment.start = -segment.start - segment.size;
+            }
+        }
+
+        segments
+    }
+}
+
+/// Defines how the edges and center of a `NineSlice` fill the space between the corners.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum NineSliceMode {
+    /// The edges and center are stretched.
+    #[default]
+    Stretch,
+    /// The edges and center are repeated, the last tile is cut off. (Stretched instead if more than
+    /// 64 tiles would be needed)
+    Tile,
+}
+
+/// A part of one axis of a `NineSlice`.
+struct Segment {
+    /// The start position in world units.
+    start: f32,
+    /// The size in world units.
+    size: f32,
+    /// The start in texture coordinates.
+    uv_start: f32,
+    /// The size in texture coordinates.
+    uv_size: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_fill_the_middle_and_cut_off_the_last_one() {
+        let nine_slice = NineSlice::new(4, 1.0).with_mode(NineSliceMode::Tile);
+        // The 16 pixel texture has an 8 pixel middle, 20 units of middle need 3 tiles and the
+        // height only fits the borders
+        let quads = nine_slice.quads(&Transform::from_scale(Vec3::new(28.0, 8.0, 1.0)), (16, 16));
+
+        assert_eq!(quads.len(), 5 * 2);
+        let last_tile = quads[3].1;
+        assert_eq!(last_tile[2], 0.5 * 4.0 / 8.0);
+    }
+
+    #[test]
+    fn too_many_tiles_are_stretched() {
+        let nine_slice = NineSlice::new(4, 0.001).with_mode(NineSliceMode::Tile);
+        let quads = nine_slice.quads(&Transform::from_scale(Vec3::new(1e6, 1e6, 1.0)), (16, 16));
+
+        assert_eq!(quads.len(), 3 * 3);
+    }
+
+    #[test]
+    fn tiny_pixel_scales_are_stretched() {
+        let nine_slice = NineSlice::new(4, f32::MIN_POSITIVE).with_mode(NineSliceMode::Tile);
+        let quads = nine_slice.quads(&Transform::from_scale(Vec3::new(2.0, 2.0, 1.0)), (16, 16));
+
+        assert_eq!(quads.len(), 3 * 3);
+    }
+
+    #[test]
+    fn non_finite_scales_draw_nothing() {
+        let nine_slice = NineSlice::new(4, 1.0).with_mode(NineSliceMode::Tile);
+
+        for scale in [f32::INFINITY, f32::NAN] {
+            let transform = Transform::from_scale(Vec3::new(scale, 8.0, 1.0));
+            assert!(nine_slice.quads(&transform, (16, 16)).is_empty());
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::ops::{Add, Mul};
+
+use bevy_ecs::prelude::*;
+use bevy_transform::components::Transform;
+use render::glam::{Mat4, Quat, Vec2, Vec3, Vec4};
+
+use crate::prelude::DeltaTime;
+
+/// Emits particles textured with the entity's `Material`. The particles are simulated in world
+/// space every frame in the `PreUpdate` schedule and are never spawned as entities. (Their
+/// instances are rebuilt every frame apart from the other instances)
+#[derive(Clone, Component)]
+pub struct ParticleEmitter {
+    /// Particles spawned per second.
+    pub spawn_rate: f32,
+    /// Particles spawned at set times after the emitter starts.
+    pub bursts: Vec<ParticleBurst>,
+    /// The maximum amount of live particles.
+    pub max_particles: usize,
+    /// If the emitter spawns new particles. (Live particles are still simulated)
+    pub emitting: bool,
+    /// The range of particle lifetimes in seconds.
+    pub lifetime: (f32, f32),
+    /// The range of initial particle velocities in world units per second.
+    pub velocity: (Vec2, Vec2),
+    /// The range of particle accelerations in world units per second squared.
+    pub gravity: (Vec2, Vec2),
+    /// The particle size in world units.
+    pub size: f32,
+    /// The particle color (RGBA) over its lifetime.
+    pub color_over_lifetime: Curve<Vec4>,
+    /// The particle size multiplier over its lifetime.
+    pub scale_over_lifetime: Curve<f32>,
+    particles: Vec<Particle>,
+    /// The time since the emitter started in seconds.
+    age: f32,
+    /// The fractional amount of particles left over from the spawn rate.
+    spawn_remainder: f32,
+    /// Particles to spawn on the next update.
+    pending_burst: u32,
+    rng: Rng,
+}
+
+impl ParticleEmitter {
+    /// Creates a new `ParticleEmitter` with white, constant size particles.
+    pub fn new(spawn_rate: f32, lifetime: (f32, f32), velocity: (Vec2, Vec2)) -> Self {
+        Self {
+            spawn_rate,
+            bursts: Vec::new(),
+            max_particles: 1000,
+            emitting: true,
+            lifetime,
+            velocity,
+            gravity: (Vec2::ZERO, Vec2::ZERO),
+            size: 1.0,
+            color_over_lifetime: Curve::constant(Vec4::ONE),
+            scale_over_lifetime: Curve::constant(1.0),
+            particles: Vec::new(),
+            age: 0.0,
+            spawn_remainder: 0.0,
+            pending_burst: 0,
+            rng: Rng::new(0x9E37_79B9_7F4A_7C15),
+        }
+    }
+
+    /// Adds a burst of particles at a set time after the emitter starts.
+    pub fn with_burst(mut self, time: f32, count: u32) -> Self {
+        self.bursts.push(ParticleBurst { time, count });
+        self
+    }
+
+    /// Sets the maximum amount of live particles.
+    pub fn with_max_particles(mut self, max_particles: usize) -> Self {
+        self.max_particles = max_particles;
+        self
+    }
+
+    /// Sets the range of particle accelerations.
+    pub fn with_gravity(mut self, gravity: (Vec2, Vec2)) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Sets the particle size in world units.
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the particle color over its lifetime.
+    pub fn with_color_over_lifetime(mut self, color_over_lifetime: Curve<Vec4>) -> Self {
+        self.color_over_lifetime = color_over_lifetime;
+        self
+    }
+
+    /// Sets the particle size multiplier over its lifetime.
+    pub fn with_scale_over_lifetime(mut self, scale_over_lifetime: Curve<f32>) -> Self {
+        self.scale_over_lifetime = scale_over_lifetime;
+        self
+    }
+
+    /// Sets the seed used to randomize the particles.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// Spawns particles on the next update.
+    pub fn burst(&mut self, count: u32) {
+        self.pending_burst += count;
+    }
+
+    /// Gets the amount of live particles.
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Simulates the live particles and spawns new ones at `origin`.
+    fn update(&mut self, origin: Vec2, delta_time: f32) {
+        self.particles.retain_mut(|particle| {
+            particle.age += delta_time;
+            particle.velocity += particle.gravity * delta_time;
+            particle.position += particle.velocity * delta_time;
+            particle.age < particle.lifetime
+        });
+
+        let mut spawn_count = std::mem::take(&mut self.pending_burst);
+        if self.emitting {
+            self.spawn_remainder += self.spawn_rate * delta_time;
+            spawn_count += self.spawn_remainder as u32;
+            self.spawn_remainder = self.spawn_remainder.fract();
+
+            let previous_age = self.age;
+            self.age += delta_time;
+            spawn_count += self
+                .bursts
+                .iter()
+                .filter(|burst| burst.time >= previous_age && burst.time < self.age)
+                .map(|burst| burst.count)
+                .sum::<u32>();
+        }
+
+        let spawn_count =
+            (spawn_count as usize).min(self.max_particles.saturating_sub(self.particles.len()));
+        for _ in 0..spawn_count {
+            let particle = Particle {
+                position: origin,
+                velocity: self.rng.range_vec2(self.velocity),
+                gravity: self.rng.range_vec2(self.gravity),
+                age: 0.0,
+                lifetime: self
+                    .rng
+                    .range(self.lifetime.0, self.lifetime.1)
+                    .max(f32::EPSILON),
+            };
+            self.particles.push(particle);
+        }
+    }
+
+    /// Gets the transform and color of every live particle.
+    pub(crate) fn particle_quads(&self, z: f32) -> impl Iterator<Item = (Mat4, [f32; 4])> + '_ {
+        self.particles.iter().map(move |particle| {
+            let t = particle.age / particle.lifetime;
+            let size = self.size * self.scale_over_lifetime.sample(t);
+
+            (
+                Mat4::from_scale_rotation_translation(
+                    Vec3::new(size, size, 1.0),
+                    Quat::IDENTITY,
+                    particle.position.extend(z),
+                ),
+                self.color_over_lifetime.sample(t).to_array(),
+            )
+        })
+    }
+}
+
+/// Particles spawned at a set time after a `ParticleEmitter` starts.
+#[derive(Debug, Clone, Copy)]
+pub struct ParticleBurst {
+    /// The time in seconds.
+    pub time: f32,
+    pub count: u32,
+}
+
+/// A piecewise linear curve over a particle's lifetime (0 to 1).
+#[derive(Debug, Clone)]
+pub struct Curve<T> {
+    /// The keyframes as `(time, value)` sorted by time.
+    keyframes: Vec<(f32, T)>,
+}
+
+impl<T> Curve<T>
+where
+    T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+{
+    /// Creates a curve from `(time, value)` keyframes. (Panics if there are no keyframes)
+    pub fn new(mut keyframes: Vec<(f32, T)>) -> Self {
+        assert!(!keyframes.is_empty(), "curve has no keyframes");
+        keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { keyframes }
+    }
+
+    /// Creates a curve with a single value.
+    pub fn constant(value: T) -> Self {
+        Self {
+            keyframes: vec![(0.0, value)],
+        }
+    }
+
+    /// Creates a curve that goes linearly from `start` to `end`.
+    pub fn linear(start: T, end: T) -> Self {
+        Self {
+            keyframes: vec![(0.0, start), (1.0, end)],
+        }
+    }
+
+    /// Gets the value at the time, clamped to the first and last keyframes.
+    pub fn sample(&self, time: f32) -> T {
+        let i = self.keyframes.partition_point(|&(t, _)| t <= time);
+        match (self.keyframes.get(i.wrapping_sub(1)), self.keyframes.get(i)) {
+            (Some(&(start_time, start)), Some(&(end_time, end))) => {
+                let t = (time - start_time) / (end_time - start_time);
+                start * (1.0 - t) + end * t
+            }
+            (Some(&(_, value)), None) | (None, Some(&(_, value))) => value,
+            (None, None) => panic!("curve has no keyframes"),
+        }
+    }
+}
+
+/// A live particle.
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    gravity: Vec2,
+    age: f32,
+    lifetime: f32,
+}
+
+/// A small xorshift random number generator.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    /// Gets a random number from 0 to 1.
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    fn range_vec2(&mut self, (min, max): (Vec2, Vec2)) -> Vec2 {
+        Vec2::new(self.range(min.x, max.x), self.range(min.y, max.y))
+    }
+}
+
+/// Simulates the particles of every emitter.
+pub(crate) fn update_particle_emitters(
+    delta_time: Res<DeltaTime>,
+    mut emitters: Query<(&Transform, &mut ParticleEmitter)>,
+) {
+    let delta_time = delta_time.0.as_secs_f32();
+    for (transform, mut emitter) in &mut emitters {
+        if emitter.emitting || !emitter.particles.is_empty() || emitter.pending_burst > 0 {
+            emitter.update(transform.translation.truncate(), delta_time);
+        }
+    }
+}
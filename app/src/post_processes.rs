@@ -0,0 +1,21 @@
+use bevy_ecs::prelude::*;
+
+pub use render::post_process::PostProcess;
+
+/// Holds the post-process passes that are applied to every frame in order.
+#[derive(Default, Resource)]
+pub struct PostProcesses {
+    pub(crate) post_processes: Vec<PostProcess>,
+    pub(crate) changed: bool,
+}
+
+impl PostProcesses {
+    pub fn get_post_processes(&self) -> &[PostProcess] {
+        &self.post_processes
+    }
+
+    pub fn get_post_processes_mut(&mut self) -> &mut Vec<PostProcess> {
+        self.changed = true;
+        &mut self.post_processes
+    }
+}
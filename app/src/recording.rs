@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+
+pub use render::recording::RecordingOutput;
+
+/// Settings of a frame recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    pub output: RecordingOutput,
+    /// The simulated time between frames, used as `DeltaTime` while recording.
+    pub step: Duration,
+    /// The number of frames to record or `None` to record until stopped.
+    pub frame_count: Option<u32>,
+}
+
+impl Recording {
+    pub fn new(output: RecordingOutput, frames_per_second: u32) -> Self {
+        Self {
+            output,
+            step: Duration::from_secs(1) / frames_per_second.max(1),
+            frame_count: None,
+        }
+    }
+
+    /// Sets the number of frames to record before stopping.
+    pub fn with_frame_count(mut self, frame_count: u32) -> Self {
+        self.frame_count = Some(frame_count);
+        self
+    }
+}
+
+/// Records the rendered frames at a fixed simulated frame rate. (Frames are written in the
+/// background after they're read back, errors are printed)
+#[derive(Default, Resource)]
+pub struct FrameRecorder {
+    pub(crate) recording: Option<Recording>,
+    pub(crate) frame: u32,
+}
+
+impl FrameRecorder {
+    /// Starts recording from the next frame, replacing the current recording.
+    pub fn start(&mut self, recording: Recording) {
+        self.recording = Some(recording);
+        self.frame = 0;
+    }
+
+    pub fn stop(&mut self) {
+        self.recording = None;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn get_recording(&self) -> Option<&Recording> {
+        self.recording.as_ref()
+    }
+
+    /// Gets the number of frames recorded so far.
+    pub fn get_frame(&self) -> u32 {
+        self.frame
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy_ecs::prelude::*;
+use bevy_transform::components::Transform;
+use render::{
+    glam::{Quat, Vec3},
+    wgpu,
+};
+use ron::{extensions::Extensions, ser::PrettyConfig};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    Camera, Handle, Material, Sampler, StrongHandle, Texture, Textures, Visibility,
+};
+
+/// A set of entities that can be saved to and loaded from a RON file. Textures are referenced by
+/// the paths they're loaded from with `Textures::load`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub entities: Vec<SceneEntity>,
+}
+
+/// The components of a scene entity. (Missing components aren't added)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneEntity {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transform: Option<SceneTransform>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub material: Option<SceneMaterial>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera: Option<SceneCamera>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneTransform {
+    pub translation: [f32; 3],
+    /// The rotation quaternion as `[x, y, z, w]`.
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+impl Default for SceneTransform {
+    fn default() -> Self {
+        Self::from(Transform::IDENTITY)
+    }
+}
+
+impl From<Transform> for SceneTransform {
+    fn from(transform: Transform) -> Self {
+        Self {
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+            scale: transform.scale.to_array(),
+        }
+    }
+}
+
+impl From<SceneTransform> for Transform {
+    fn from(transform: SceneTransform) -> Self {
+        Transform {
+            translation: Vec3::from_array(transform.translation),
+            rotation: Quat::from_array(transform.rotation),
+            scale: Vec3::from_array(transform.scale),
+        }
+    }
+}
+
+/// A `Material` with its textures referenced by path. (Drawn with the main shader and a default
+/// sampler)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneMaterial {
+    pub texture: PathBuf,
+    #[serde(default = "full_uv_rect")]
+    pub uv_rect: [f32; 4],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normal_map: Option<PathBuf>,
+}
+
+fn full_uv_rect() -> [f32; 4] {
+    [0.0, 0.0, 1.0, 1.0]
+}
+
+/// A `Camera` that draws to the window.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SceneCamera {
+    pub vertical_scale: f32,
+    pub near_clip: f32,
+    pub far_clip: f32,
+    /// The clear color as `[r, g, b, a]` or `None` to not clear.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clear_color: Option<[f64; 4]>,
+}
+
+impl From<Camera> for SceneCamera {
+    fn from(camera: Camera) -> Self {
+        Self {
+            vertical_scale: camera.vertical_scale,
+            near_clip: camera.near_clip,
+            far_clip: camera.far_clip,
+            clear_color: Some([
+                camera.clear_color.r,
+                camera.clear_color.g,
+                camera.clear_color.b,
+                camera.clear_color.a,
+            ]),
+        }
+    }
+}
+
+impl From<SceneCamera> for Camera {
+    fn from(camera: SceneCamera) -> Self {
+        let [r, g, b, a] = camera.clear_color.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+        Camera {
+            vertical_scale: camera.vertical_scale,
+            near_clip: camera.near_clip,
+            far_clip: camera.far_clip,
+            clear_color: wgpu::Color { r, g, b, a },
+            target: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    /// The file couldn't be read or written.
+    Io(io::Error),
+    /// The file isn't a valid scene.
+    Parse(ron::error::SpannedError),
+    /// The scene couldn't be written as RON.
+    Serialize(ron::Error),
+    /// A material of the entity uses a texture that wasn't loaded from a file.
+    TextureWithoutPath(Entity),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(error) => write!(f, "failed to access scene file: {error}"),
+            SceneError::Parse(error) => write!(f, "failed to parse scene: {error}"),
+            SceneError::Serialize(error) => write!(f, "failed to serialize scene: {error}"),
+            SceneError::TextureWithoutPath(entity) => {
+                write!(
+                    f,
+                    "entity {entity} uses a texture that wasn't loaded from a file"
+                )
+            }
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+impl Scene {
+    /// Parses a scene from RON. (`Some(...)` can be left out of optional fields)
+    pub fn from_ron(source: &str) -> Result<Self, SceneError> {
+        ron::Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_str(source)
+            .map_err(SceneError::Parse)
+    }
+
+    /// Writes the scene as pretty RON.
+    pub fn to_ron(&self) -> Result<String, SceneError> {
+        let config = PrettyConfig::new().extensions(Extensions::IMPLICIT_SOME);
+        ron::Options::default()
+            .to_string_pretty(self, config)
+            .map_err(SceneError::Serialize)
+    }
+
+    /// Reads a scene from a RON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        let source = fs::read_to_string(path).map_err(SceneError::Io)?;
+        Self::from_ron(&source)
+    }
+
+    /// Writes the scene to a RON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SceneError> {
+        fs::write(path, self.to_ron()?).map_err(SceneError::Io)
+    }
+
+    /// Creates a scene from the components of the entities. (Entities that don't exist are
+    /// skipped)
+    pub fn snapshot(
+        world: &World,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> Result<Self, SceneError> {
+        let textures = world.resource::<Textures>();
+        let texture_path = |entity, texture: &Handle<Texture>| {
+            textures
+                .get_path(texture.index())
+                .map(Path::to_path_buf)
+                .ok_or(SceneError::TextureWithoutPath(entity))
+        };
+
+        let mut scene_entities = Vec::new();
+        for entity in entities {
+            let Ok(entity_ref) = world.get_entity(entity) else {
+                continue;
+            };
+
+            let material = match entity_ref.get::<Material>() {
+                Some(material) => Some(SceneMaterial {
+                    texture: texture_path(entity, &material.texture)?,
+                    uv_rect: material.uv_rect,
+                    normal_map: material
+                        .normal_map
+                        .as_ref()
+                        .map(|normal_map| texture_path(entity, normal_map))
+                        .transpose()?,
+                }),
+                None => None,
+            };
+
+            scene_entities.push(SceneEntity {
+                transform: entity_ref
+                    .get::<Transform>()
+                    .map(|&transform| transform.into()),
+                material,
+                visibility: entity_ref.get::<Visibility>().copied(),
+                camera: entity_ref.get::<Camera>().map(|&camera| camera.into()),
+            });
+        }
+
+        Ok(Self {
+            entities: scene_entities,
+        })
+    }
+
+    /// Spawns the scene's entities and returns them in order. Textures that aren't loaded yet are
+    /// loaded in the background.
+    pub fn spawn(&self, world: &mut World) -> Vec<Entity> {
+        let mut textures = world.resource_mut::<Textures>();
+        let mut sampler: Option<StrongHandle<Sampler>> = None;
+        let mut loaded_textures = HashMap::new();
+
+        let materials: Vec<_> = self
+            .entities
+            .iter()
+            .map(|entity| {
+                let scene_material = entity.material.as_ref()?;
+                let mut load = |path: &PathBuf| {
+                    loaded_textures
+                        .entry(path.clone())
+                        .or_insert_with(|| textures.get_or_load(path))
+                        .clone()
+                };
+
+                let texture = load(&scene_material.texture);
+                let normal_map = scene_material.normal_map.as_ref().map(load);
+                let sampler = sampler
+                    .get_or_insert_with(|| textures.get_samplers_mut().push_strong(Sampler))
+                    .clone();
+
+                let mut material =
+                    Material::new(texture, sampler).with_uv_rect(scene_material.uv_rect);
+                if let Some(normal_map) = normal_map {
+                    material = material.with_normal_map(normal_map);
+                }
+
+                Some(material)
+            })
+            .collect();
+
+        self.entities
+            .iter()
+            .zip(materials)
+            .map(|(scene_entity, material)| {
+                let mut entity = world.spawn_empty();
+                if let Some(transform) = scene_entity.transform {
+                    entity.insert(Transform::from(transform));
+                }
+                if let Some(material) = material {
+                    entity.insert(material);
+                }
+                if let Some(visibility) = scene_entity.visibility {
+                    entity.insert(visibility);
+                }
+                if let Some(camera) = scene_entity.camera {
+                    entity.insert(Camera::from(camera));
+                }
+
+                entity.id()
+            })
+            .collect()
+    }
+}
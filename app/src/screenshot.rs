@@ -0,0 +1,16 @@
+use std::path::PathBuf;
+
+use bevy_ecs::prelude::*;
+
+/// Send this event to save the next frame as a PNG file. (Saved in the background after the
+/// frame is read back, errors are printed)
+#[derive(Clone, Event)]
+pub struct Screenshot {
+    pub path: PathBuf,
+}
+
+impl Screenshot {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
@@ -0,0 +1,23 @@
+use bevy_ecs::prelude::*;
+
+use crate::prelude::DenseStorage;
+
+pub use render::shader::{Shader, ShaderHotReload, ShaderSource};
+
+/// Holds the custom shaders that materials can use.
+#[derive(Default, Resource)]
+pub struct Shaders {
+    pub(crate) shaders: DenseStorage<Shader>,
+    pub(crate) changed: bool,
+}
+
+impl Shaders {
+    pub fn get_shaders(&self) -> &DenseStorage<Shader> {
+        &self.shaders
+    }
+
+    pub fn get_shaders_mut(&mut self) -> &mut DenseStorage<Shader> {
+        self.changed = true;
+        &mut self.shaders
+    }
+}
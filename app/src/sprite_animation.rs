@@ -0,0 +1,241 @@
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+
+use crate::prelude::{DeltaTime, Handle, Material, Texture};
+
+/// Animates the entity's `Material` by switching between frames. (Advanced every frame in the
+/// `PreUpdate` schedule)
+#[derive(Clone, Component)]
+pub struct SpriteAnimation {
+    pub frames: Vec<AnimationFrame>,
+    /// How long each frame is shown at a speed of 1.
+    pub frame_duration: Duration,
+    pub mode: AnimationMode,
+    /// The playback speed multiplier.
+    pub speed: f32,
+    /// If the animation is paused.
+    pub paused: bool,
+    /// The time spent on the current frame.
+    elapsed: Duration,
+    /// The index of the current frame.
+    frame: usize,
+    /// If a `PingPong` animation is playing backwards.
+    reversed: bool,
+    /// If a `Once` animation has reached its last frame.
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    /// Creates a new looping `SpriteAnimation` with a speed of 1.
+    pub fn new(frames: Vec<AnimationFrame>, frame_duration: Duration) -> Self {
+        Self {
+            frames,
+            frame_duration,
+            mode: AnimationMode::Loop,
+            speed: 1.0,
+            paused: false,
+            elapsed: Duration::ZERO,
+            frame: 0,
+            reversed: false,
+            finished: false,
+        }
+    }
+
+    /// Creates a new looping `SpriteAnimation` from the cells of a sprite sheet, read left to
+    /// right and top to bottom.
+    pub fn from_sheet(
+        texture: impl Into<Handle<Texture>>,
+        columns: u32,
+        rows: u32,
+        frame_duration: Duration,
+    ) -> Self {
+        let texture = texture.into();
+        let cell_size = (1.0 / columns.max(1) as f32, 1.0 / rows.max(1) as f32);
+        let frames = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .map(|(column, row)| {
+                AnimationFrame::region(
+                    texture.clone(),
+                    [
+                        column as f32 * cell_size.0,
+                        row as f32 * cell_size.1,
+                        cell_size.0,
+                        cell_size.1,
+                    ],
+                )
+            })
+            .collect();
+
+        Self::new(frames, frame_duration)
+    }
+
+    /// Sets the playback mode.
+    pub fn with_mode(mut self, mode: AnimationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the playback speed multiplier.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Gets the index of the current frame.
+    pub fn get_frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Gets if a `Once` animation has finished.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Restarts the animation from the first frame.
+    pub fn restart(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.frame = 0;
+        self.reversed = false;
+        self.finished = false;
+    }
+
+    /// Advances the animation by `delta_time` and returns if it finished during this step.
+    fn advance(&mut self, delta_time: Duration) -> bool {
+        if self.paused || self.finished {
+            return false;
+        }
+
+        let last_frame = self.frames.len().saturating_sub(1);
+        if self.frame == last_frame || self.frame_duration.is_zero() {
+            // `Once` animations without frames to advance through finish right away
+            if self.mode == AnimationMode::Once {
+                self.frame = last_frame;
+                self.finished = true;
+                self.elapsed = Duration::ZERO;
+                return true;
+            }
+            if self.frames.len() < 2 || self.frame_duration.is_zero() {
+                return false;
+            }
+        }
+
+        // Infinite or huge speeds saturate instead of overflowing
+        let scaled_delta_time =
+            Duration::try_from_secs_f64(delta_time.as_secs_f64() * f64::from(self.speed.max(0.0)))
+                .unwrap_or(Duration::MAX);
+        self.elapsed = self.elapsed.saturating_add(scaled_delta_time);
+
+        // Skip whole cycles so large steps don't advance frame by frame
+        let cycle_frames = match self.mode {
+            AnimationMode::Loop => self.frames.len() as u128,
+            AnimationMode::PingPong => 2 * last_frame as u128,
+            AnimationMode::Once => u128::MAX,
+        };
+        let cycle = self.frame_duration.as_nanos().saturating_mul(cycle_frames);
+        let elapsed = self.elapsed.as_nanos();
+        if elapsed >= cycle {
+            let remainder = elapsed % cycle;
+            self.elapsed = Duration::new(
+                (remainder / 1_000_000_000) as u64,
+                (remainder % 1_000_000_000) as u32,
+            );
+        }
+
+        while self.elapsed >= self.frame_duration {
+            self.elapsed -= self.frame_duration;
+
+            match self.mode {
+                AnimationMode::Loop => self.frame = (self.frame + 1) % self.frames.len(),
+                AnimationMode::PingPong => {
+                    if self.reversed && self.frame == 0
+                        || !self.reversed && self.frame == last_frame
+                    {
+                        self.reversed = !self.reversed;
+                    }
+
+                    if self.reversed {
+                        self.frame -= 1;
+                    } else {
+                        self.frame += 1;
+                    }
+                }
+                AnimationMode::Once => {
+                    self.frame += 1;
+                    if self.frame == last_frame {
+                        self.finished = true;
+                        self.elapsed = Duration::ZERO;
+                        return true;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// A frame of a `SpriteAnimation`.
+#[derive(Clone)]
+pub struct AnimationFrame {
+    pub texture: Handle<Texture>,
+    /// The region of the texture to draw as `[u, v, width, height]` in texture coordinates.
+    pub uv_rect: [f32; 4],
+}
+
+impl AnimationFrame {
+    /// Creates a frame that shows the whole texture.
+    pub fn texture(texture: impl Into<Handle<Texture>>) -> Self {
+        Self {
+            texture: texture.into(),
+            uv_rect: [0.0, 0.0, 1.0, 1.0],
+        }
+    }
+
+    /// Creates a frame that shows a region of the texture.
+    pub fn region(texture: impl Into<Handle<Texture>>, uv_rect: [f32; 4]) -> Self {
+        Self {
+            texture: texture.into(),
+            uv_rect,
+        }
+    }
+}
+
+/// Defines what a `SpriteAnimation` does after its last frame.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationMode {
+    /// Starts again from the first frame.
+    #[default]
+    Loop,
+    /// Plays backwards to the first frame, then forwards again.
+    PingPong,
+    /// Stops on the last frame and sends an `AnimationFinished` event.
+    Once,
+}
+
+/// Sent when a `SpriteAnimation` with `AnimationMode::Once` reaches its last frame.
+#[derive(Clone, Copy, Event)]
+pub struct AnimationFinished(pub Entity);
+
+/// Advances the sprite animations and updates their materials.
+pub(crate) fn advance_sprite_animations(
+    delta_time: Res<DeltaTime>,
+    mut animations: Query<(Entity, &mut SpriteAnimation, &mut Material)>,
+    mut animation_finished: EventWriter<AnimationFinished>,
+) {
+    for (entity, mut animation, mut material) in &mut animations {
+        if animation.advance(delta_time.0) {
+            animation_finished.write(AnimationFinished(entity));
+        }
+
+        let Some(frame) = animation.frames.get(animation.frame) else {
+            continue;
+        };
+
+        // Only touch the material when the frame changes to keep change detection quiet
+        if material.texture != frame.texture || material.uv_rect != frame.uv_rect {
+            material.texture = frame.texture.clone();
+            material.uv_rect = frame.uv_rect;
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use bevy_ecs::prelude::*;
+use bevy_transform::components::Transform;
+use render::{
+    glam::{Mat4, Quat, Vec3},
+    wgpu,
+};
+
+use crate::prelude::{BitmapFont, DenseStorageIndex, Sampler, Texture};
+
+/// Draws a string with a bitmap font. The text starts at the entity's `Transform` with the top of
+/// the first line at the origin.
+#[derive(Clone, Component)]
+#[require(Transform)]
+pub struct Text {
+    pub text: String,
+    pub font: DenseStorageIndex<BitmapFont>,
+    pub sampler: DenseStorageIndex<Sampler>,
+    /// The color multiplied with the font texture.
+    pub color: wgpu::Color,
+    /// The height of a line in world units.
+    pub size: f32,
+    /// The distance between lines relative to the line height.
+    pub line_spacing: f32,
+    pub alignment: TextAlignment,
+}
+
+impl Text {
+    /// Creates a new white, left aligned `Text` with a size of 1.
+    pub fn new(
+        text: impl Into<String>,
+        font: DenseStorageIndex<BitmapFont>,
+        sampler: DenseStorageIndex<Sampler>,
+    ) -> Self {
+        Self {
+            text: text.into(),
+            font,
+            sampler,
+            color: wgpu::Color::WHITE,
+            size: 1.0,
+            line_spacing: 1.0,
+            alignment: TextAlignment::Left,
+        }
+    }
+
+    /// Sets the color.
+    pub fn with_color(mut self, color: wgpu::Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the height of a line in world units.
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the distance between lines relative to the line height.
+    pub fn with_line_spacing(mut self, line_spacing: f32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    /// Sets the horizontal alignment of the lines.
+    pub fn with_alignment(mut self, alignment: TextAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Lays out the glyphs and gets the transform, uv rect and page texture of each one.
+    pub(crate) fn glyph_quads(
+        &self,
+        transform: &Transform,
+        font: &BitmapFont,
+    ) -> Vec<(Mat4, [f32; 4], DenseStorageIndex<Texture>)> {
+        let matrix = transform.compute_matrix();
+        let scale = self.size / font.line_height.max(1) as f32;
+        let line_height = font.line_height as f32 * scale * self.line_spacing;
+        let page_scale = (font.scale.0.max(1) as f32, font.scale.1.max(1) as f32);
+
+        let mut quads = Vec::new();
+        for (line_index, line) in self.text.lines().enumerate() {
+            let line_width = line_width(line, font) as f32 * scale;
+            let mut pen = match self.alignment {
+                TextAlignment::Left => 0.0,
+                TextAlignment::Center => -line_width * 0.5,
+                TextAlignment::Right => -line_width,
+            };
+            let line_top = -(line_index as f32) * line_height;
+
+            let mut previous = None;
+            for character in line.chars() {
+                let Some(glyph) = font.glyphs.get(&character) else {
+                    continue;
+                };
+
+                if let Some(previous) = previous {
+                    pen += *font.kernings.get(&(previous, character)).unwrap_or(&0) as f32 * scale;
+                }
+                previous = Some(character);
+
+                let size = (glyph.size.0 as f32 * scale, glyph.size.1 as f32 * scale);
+                if let Some(&page_texture) = font.page_textures.get(glyph.page)
+                    && size.0 > 0.0
+                    && size.1 > 0.0
+                {
+                    let quad = Mat4::from_scale_rotation_translation(
+                        Vec3::new(size.0, size.1, 1.0),
+                        Quat::IDENTITY,
+                        Vec3::new(
+                            pen + glyph.offset.0 as f32 * scale + size.0 * 0.5,
+                            line_top - glyph.offset.1 as f32 * scale - size.1 * 0.5,
+                            0.0,
+                        ),
+                    );
+                    let uv_rect = [
+                        glyph.position.0 as f32 / page_scale.0,
+                        glyph.position.1 as f32 / page_scale.1,
+                        glyph.size.0 as f32 / page_scale.0,
+                        glyph.size.1 as f32 / page_scale.1,
+                    ];
+
+                    quads.push((matrix * quad, uv_rect, page_texture));
+                }
+
+                pen += glyph.advance as f32 * scale;
+            }
+        }
+
+        quads
+    }
+}
+
+/// Gets the width of a line in font pixels.
+fn line_width(line: &str, font: &BitmapFont) -> i32 {
+    let mut width = 0;
+    let mut previous = None;
+    for character in line.chars() {
+        let Some(glyph) = font.glyphs.get(&character) else {
+            continue;
+        };
+
+        if let Some(previous) = previous {
+            width += font.kernings.get(&(previous, character)).unwrap_or(&0);
+        }
+        previous = Some(character);
+
+        width += glyph.advance;
+    }
+
+    width
+}
+
+/// Defines how the lines of a `Text` are aligned to its origin.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlignment {
+    /// The lines start at the origin.
+    #[default]
+    Left,
+    /// The lines are centered on the origin.
+    Center,
+    /// The lines end at the origin.
+    Right,
+}
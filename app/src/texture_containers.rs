@@ -0,0 +1,296 @@
+use crate::prelude::{Texture, TextureFormat, TextureLoadError};
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xab, b'K', b'T', b'X', b' ', b'2', b'0', 0xbb, b'\r', b'\n', 0x1a, b'\n',
+];
+
+/// Parses a KTX2 file with a single 2D image and its mip levels. (Supercompressed files aren't
+/// supported)
+pub fn parse_ktx2(bytes: &[u8]) -> Result<Texture, TextureLoadError> {
+    if bytes.get(..12) != Some(&KTX2_IDENTIFIER[..]) {
+        return Err(TextureLoadError::InvalidContainer(
+            "missing KTX2 identifier",
+        ));
+    }
+
+    let vk_format = read_u32(bytes, 12)?;
+    let size = (read_u32(bytes, 20)?, read_u32(bytes, 24)?);
+    let depth = read_u32(bytes, 28)?;
+    let layer_count = read_u32(bytes, 32)?;
+    let face_count = read_u32(bytes, 36)?;
+    // 0 asks for the mip levels to be generated at runtime, which isn't supported
+    let mip_level_count = read_u32(bytes, 40)?.max(1);
+    let supercompression = read_u32(bytes, 44)?;
+
+    if depth > 0 || layer_count > 1 || face_count > 1 {
+        return Err(TextureLoadError::UnsupportedFormat(
+            "KTX2 textures other than a single 2D image".to_string(),
+        ));
+    }
+    if supercompression != 0 {
+        return Err(TextureLoadError::UnsupportedFormat(format!(
+            "KTX2 supercompression scheme {supercompression}"
+        )));
+    }
+
+    let format = vk_format_to_texture_format(vk_format)
+        .ok_or_else(|| TextureLoadError::UnsupportedFormat(format!("Vulkan format {vk_format}")))?;
+
+    // The level index follows the 80 byte header, starting from the largest level
+    let mut data = Vec::new();
+    for level in 0..mip_level_count as usize {
+        let offset = read_u64(bytes, 80 + level * 24)?;
+        let length = read_u64(bytes, 88 + level * 24)?;
+        let level_data = usize::try_from(offset)
+            .ok()
+            .zip(usize::try_from(length).ok())
+            .and_then(|(offset, length)| bytes.get(offset..offset.checked_add(length)?))
+            .ok_or(TextureLoadError::InvalidContainer(
+                "KTX2 mip level is out of bounds",
+            ))?;
+        data.extend_from_slice(level_data);
+    }
+
+    let texture = Texture::new(size, data)
+        .with_format(format)
+        .with_mip_level_count(mip_level_count);
+    texture.validate().map_err(TextureLoadError::InvalidData)?;
+    Ok(texture)
+}
+
+/// Maps the Vulkan formats of KTX2 files. (BC1 without alpha is read as BC1 with alpha like most
+/// tools do)
+fn vk_format_to_texture_format(vk_format: u32) -> Option<TextureFormat> {
+    Some(match vk_format {
+        9 => TextureFormat::R8,
+        16 => TextureFormat::Rg8,
+        37 => TextureFormat::Rgba8Unorm,
+        43 => TextureFormat::Rgba8Srgb,
+        97 => TextureFormat::Rgba16Float,
+        131 | 133 => TextureFormat::Bc1RgbaUnorm,
+        132 | 134 => TextureFormat::Bc1RgbaSrgb,
+        135 => TextureFormat::Bc2RgbaUnorm,
+        136 => TextureFormat::Bc2RgbaSrgb,
+        137 => TextureFormat::Bc3RgbaUnorm,
+        138 => TextureFormat::Bc3RgbaSrgb,
+        139 => TextureFormat::Bc4RUnorm,
+        141 => TextureFormat::Bc5RgUnorm,
+        145 => TextureFormat::Bc7RgbaUnorm,
+        146 => TextureFormat::Bc7RgbaSrgb,
+        147 => TextureFormat::Etc2Rgb8Unorm,
+        148 => TextureFormat::Etc2Rgb8Srgb,
+        149 => TextureFormat::Etc2Rgb8A1Unorm,
+        150 => TextureFormat::Etc2Rgb8A1Srgb,
+        151 => TextureFormat::Etc2Rgba8Unorm,
+        152 => TextureFormat::Etc2Rgba8Srgb,
+        _ => return None,
+    })
+}
+
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+/// The size of the magic number and header.
+const DDS_HEADER_SIZE: usize = 128;
+/// The size of the DX10 header extension.
+const DDS_DX10_HEADER_SIZE: usize = 20;
+
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+const D3D10_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+
+/// Parses a DDS file with a single 2D image and its mip levels.
+///
+/// Legacy files without the DX10 header have no color space, their color formats are read as
+/// sRGB like PNGs.
+pub fn parse_dds(bytes: &[u8]) -> Result<Texture, TextureLoadError> {
+    if bytes.get(..4) != Some(&DDS_MAGIC[..]) {
+        return Err(TextureLoadError::InvalidContainer(
+            "missing DDS magic number",
+        ));
+    }
+
+    let flags = read_u32(bytes, 8)?;
+    let size = (read_u32(bytes, 16)?, read_u32(bytes, 12)?);
+    let mip_level_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        read_u32(bytes, 28)?.max(1)
+    } else {
+        1
+    };
+    let pixel_format_flags = read_u32(bytes, 80)?;
+    let caps2 = read_u32(bytes, 112)?;
+    let four_cc = &bytes[84..88];
+
+    if caps2 & (DDSCAPS2_CUBEMAP | DDSCAPS2_VOLUME) != 0 {
+        return Err(TextureLoadError::UnsupportedFormat(
+            "DDS cubemaps and volume textures".to_string(),
+        ));
+    }
+
+    let mut swap_red_blue = false;
+    let (format, data_offset) = if pixel_format_flags & DDPF_FOURCC != 0 {
+        match four_cc {
+            b"DX10" => {
+                let dxgi_format = read_u32(bytes, DDS_HEADER_SIZE)?;
+                let dimension = read_u32(bytes, DDS_HEADER_SIZE + 4)?;
+                let array_size = read_u32(bytes, DDS_HEADER_SIZE + 12)?;
+                if dimension != D3D10_RESOURCE_DIMENSION_TEXTURE2D || array_size > 1 {
+                    return Err(TextureLoadError::UnsupportedFormat(
+                        "DDS textures other than a single 2D image".to_string(),
+                    ));
+                }
+
+                let format = dxgi_format_to_texture_format(dxgi_format).ok_or_else(|| {
+                    TextureLoadError::UnsupportedFormat(format!("DXGI format {dxgi_format}"))
+                })?;
+                (format, DDS_HEADER_SIZE + DDS_DX10_HEADER_SIZE)
+            }
+            b"DXT1" => (TextureFormat::Bc1RgbaSrgb, DDS_HEADER_SIZE),
+            b"DXT2" | b"DXT3" => (TextureFormat::Bc2RgbaSrgb, DDS_HEADER_SIZE),
+            b"DXT4" | b"DXT5" => (TextureFormat::Bc3RgbaSrgb, DDS_HEADER_SIZE),
+            b"ATI1" | b"BC4U" => (TextureFormat::Bc4RUnorm, DDS_HEADER_SIZE),
+            b"ATI2" | b"BC5U" => (TextureFormat::Bc5RgUnorm, DDS_HEADER_SIZE),
+            _ => {
+                return Err(TextureLoadError::UnsupportedFormat(format!(
+                    "DDS four character code {}",
+                    String::from_utf8_lossy(four_cc)
+                )));
+            }
+        }
+    } else {
+        let bit_count = read_u32(bytes, 88)?;
+        let masks = [
+            read_u32(bytes, 92)?,
+            read_u32(bytes, 96)?,
+            read_u32(bytes, 100)?,
+            read_u32(bytes, 104)?,
+        ];
+        match (pixel_format_flags & DDPF_RGB != 0, bit_count, masks) {
+            (true, 32, [0xff, 0xff00, 0xff0000, 0xff000000]) => {}
+            (true, 32, [0xff0000, 0xff00, 0xff, 0xff000000]) => swap_red_blue = true,
+            _ => {
+                return Err(TextureLoadError::UnsupportedFormat(
+                    "DDS pixel formats other than 32-bit RGBA or BGRA".to_string(),
+                ));
+            }
+        }
+        (TextureFormat::Rgba8Srgb, DDS_HEADER_SIZE)
+    };
+
+    // Any data after the mip levels is ignored
+    let data = bytes
+        .get(data_offset..)
+        .ok_or(TextureLoadError::InvalidContainer("truncated DDS header"))?;
+    let mut texture = Texture::new(size, data.to_vec())
+        .with_format(format)
+        .with_mip_level_count(mip_level_count);
+    texture
+        .validate_header()
+        .map_err(TextureLoadError::InvalidData)?;
+
+    if let Some(length) = texture
+        .mip_level_layouts()
+        .and_then(|layouts| layouts.last().map(|layout| layout.offset + layout.len()))
+    {
+        texture.data.truncate(length);
+    }
+    texture.validate().map_err(TextureLoadError::InvalidData)?;
+
+    if swap_red_blue {
+        for pixel in texture.data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    Ok(texture)
+}
+
+fn dxgi_format_to_texture_format(dxgi_format: u32) -> Option<TextureFormat> {
+    Some(match dxgi_format {
+        10 => TextureFormat::Rgba16Float,
+        28 => TextureFormat::Rgba8Unorm,
+        29 => TextureFormat::Rgba8Srgb,
+        49 => TextureFormat::Rg8,
+        61 => TextureFormat::R8,
+        71 => TextureFormat::Bc1RgbaUnorm,
+        72 => TextureFormat::Bc1RgbaSrgb,
+        74 => TextureFormat::Bc2RgbaUnorm,
+        75 => TextureFormat::Bc2RgbaSrgb,
+        77 => TextureFormat::Bc3RgbaUnorm,
+        78 => TextureFormat::Bc3RgbaSrgb,
+        80 => TextureFormat::Bc4RUnorm,
+        83 => TextureFormat::Bc5RgUnorm,
+        98 => TextureFormat::Bc7RgbaUnorm,
+        99 => TextureFormat::Bc7RgbaSrgb,
+        _ => return None,
+    })
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, TextureLoadError> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(TextureLoadError::InvalidContainer("truncated header"))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, TextureLoadError> {
+    bytes
+        .get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(TextureLoadError::InvalidContainer("truncated header"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::TextureDataError;
+
+    /// Builds a legacy DDS header for a DXT1 texture followed by `data`.
+    fn dxt1_dds(size: (u32, u32), mip_level_count: u32, data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0; DDS_HEADER_SIZE];
+        bytes[..4].copy_from_slice(DDS_MAGIC);
+        bytes[8..12].copy_from_slice(&DDSD_MIPMAPCOUNT.to_le_bytes());
+        bytes[12..16].copy_from_slice(&size.1.to_le_bytes());
+        bytes[16..20].copy_from_slice(&size.0.to_le_bytes());
+        bytes[28..32].copy_from_slice(&mip_level_count.to_le_bytes());
+        bytes[80..84].copy_from_slice(&DDPF_FOURCC.to_le_bytes());
+        bytes[84..88].copy_from_slice(b"DXT1");
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn dds_with_mip_levels_is_parsed_and_trailing_data_is_ignored() {
+        // 8x8, 4x4, 2x2 and 1x1 levels take 4, 1, 1 and 1 blocks
+        let texture = parse_dds(&dxt1_dds((8, 8), 4, &[0; 7 * 8 + 3])).unwrap();
+
+        assert_eq!(texture.format, TextureFormat::Bc1RgbaSrgb);
+        assert_eq!(texture.mip_level_count, 4);
+        assert_eq!(texture.data.len(), 7 * 8);
+    }
+
+    #[test]
+    fn dds_with_too_many_mip_levels_is_rejected() {
+        let result = parse_dds(&dxt1_dds((8, 8), u32::MAX, &[0; 64]));
+
+        assert!(matches!(
+            result,
+            Err(TextureLoadError::InvalidData(
+                TextureDataError::WrongMipLevelCount { max: 4, .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn dds_with_partial_blocks_near_the_size_limit_is_rejected() {
+        let result = parse_dds(&dxt1_dds((u32::MAX, 4), 1, &[0; 64]));
+
+        assert!(matches!(
+            result,
+            Err(TextureLoadError::InvalidData(
+                TextureDataError::PartialBlocks
+            ))
+        ));
+    }
+}
@@ -0,0 +1,1024 @@
+use crate::prelude::{Texture, TextureFormat};
+
+/// Decompresses a valid block-compressed texture, keeping its mip levels.
+pub(crate) fn decompress(texture: &Texture) -> Texture {
+    let format = texture.format.decompressed();
+    let channels = format.block_size().1 as usize;
+    let layouts = texture
+        .mip_level_layouts()
+        .expect("validated textures have mip level layouts");
+
+    let mut data = Vec::new();
+    for (level, layout) in layouts.iter().enumerate() {
+        let (width, height) = texture.mip_level_size(level as u32);
+        let level_data = &texture.data[layout.offset..layout.offset + layout.len()];
+        let blocks_wide = layout.physical_size.0 as usize / 4;
+        let block_bytes = texture.format.block_size().1 as usize;
+
+        let mut level_pixels = vec![0; width as usize * height as usize * channels];
+        for (i, block) in level_data.chunks_exact(block_bytes).enumerate() {
+            let pixels = decode_block(texture.format, block);
+            let (block_x, block_y) = ((i % blocks_wide) * 4, (i / blocks_wide) * 4);
+
+            for (j, pixel) in pixels.iter().enumerate() {
+                let (x, y) = (block_x + j % 4, block_y + j / 4);
+                if x >= width as usize || y >= height as usize {
+                    continue;
+                }
+
+                let start = (y * width as usize + x) * channels;
+                level_pixels[start..start + channels].copy_from_slice(&pixel[..channels]);
+            }
+        }
+        data.extend(level_pixels);
+    }
+
+    Texture::new(texture.size, data)
+        .with_format(format)
+        .with_mip_level_count(texture.mip_level_count)
+}
+
+/// Decodes a block into its pixels as RGBA in rows from the top. (Single and dual channel
+/// formats only fill the first channels)
+fn decode_block(format: TextureFormat, block: &[u8]) -> [[u8; 4]; 16] {
+    match format {
+        TextureFormat::Bc1RgbaSrgb | TextureFormat::Bc1RgbaUnorm => bc1(block, true),
+        TextureFormat::Bc2RgbaSrgb | TextureFormat::Bc2RgbaUnorm => {
+            let mut pixels = bc1(&block[8..], false);
+            let alpha = u64::from_le_bytes(block[..8].try_into().unwrap());
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                pixel[3] = ((alpha >> (4 * i)) & 0xf) as u8 * 17;
+            }
+            pixels
+        }
+        TextureFormat::Bc3RgbaSrgb | TextureFormat::Bc3RgbaUnorm => {
+            let mut pixels = bc1(&block[8..], false);
+            for (pixel, alpha) in pixels.iter_mut().zip(bc4(&block[..8])) {
+                pixel[3] = alpha;
+            }
+            pixels
+        }
+        TextureFormat::Bc4RUnorm => bc4(block).map(|red| [red, 0, 0, 255]),
+        TextureFormat::Bc5RgUnorm => {
+            let mut pixels = [[0, 0, 0, 255]; 16];
+            for (i, (red, green)) in bc4(&block[..8])
+                .into_iter()
+                .zip(bc4(&block[8..]))
+                .enumerate()
+            {
+                pixels[i][0] = red;
+                pixels[i][1] = green;
+            }
+            pixels
+        }
+        TextureFormat::Bc7RgbaSrgb | TextureFormat::Bc7RgbaUnorm => bc7(block),
+        TextureFormat::Etc2Rgb8Srgb | TextureFormat::Etc2Rgb8Unorm => etc2(block, false),
+        TextureFormat::Etc2Rgb8A1Srgb | TextureFormat::Etc2Rgb8A1Unorm => etc2(block, true),
+        TextureFormat::Etc2Rgba8Srgb | TextureFormat::Etc2Rgba8Unorm => {
+            let mut pixels = etc2(&block[8..], false);
+            for (pixel, alpha) in pixels.iter_mut().zip(eac(&block[..8])) {
+                pixel[3] = alpha;
+            }
+            pixels
+        }
+        TextureFormat::Rgba8Srgb
+        | TextureFormat::Rgba8Unorm
+        | TextureFormat::R8
+        | TextureFormat::Rg8
+        | TextureFormat::Rgba16Float => unreachable!("uncompressed formats have no blocks"),
+    }
+}
+
+/// Decodes a BC1 color block. (BC2 and BC3 color blocks always use four colors)
+fn bc1(block: &[u8], allow_transparent: bool) -> [[u8; 4]; 16] {
+    let color0 = u16::from_le_bytes([block[0], block[1]]);
+    let color1 = u16::from_le_bytes([block[2], block[3]]);
+    let (endpoint0, endpoint1) = (rgb565(color0), rgb565(color1));
+
+    let mix = |weight0: u32, weight1: u32| {
+        let mut color = [0, 0, 0, 255];
+        for channel in 0..3 {
+            color[channel] = ((endpoint0[channel] as u32 * weight0
+                + endpoint1[channel] as u32 * weight1)
+                / (weight0 + weight1)) as u8;
+        }
+        color
+    };
+    let palette = if color0 > color1 || !allow_transparent {
+        [endpoint0, endpoint1, mix(2, 1), mix(1, 2)]
+    } else {
+        [endpoint0, endpoint1, mix(1, 1), [0, 0, 0, 0]]
+    };
+
+    let indices = u32::from_le_bytes(block[4..8].try_into().unwrap());
+    std::array::from_fn(|i| palette[((indices >> (2 * i)) & 0b11) as usize])
+}
+
+fn rgb565(color: u16) -> [u8; 4] {
+    let red = ((color >> 11) & 0x1f) as u8;
+    let green = ((color >> 5) & 0x3f) as u8;
+    let blue = (color & 0x1f) as u8;
+
+    [
+        (red << 3) | (red >> 2),
+        (green << 2) | (green >> 4),
+        (blue << 3) | (blue >> 2),
+        255,
+    ]
+}
+
+/// Decodes a BC4 block, also used for the alpha of BC3 and each channel of BC5.
+fn bc4(block: &[u8]) -> [u8; 16] {
+    let (value0, value1) = (block[0] as u32, block[1] as u32);
+    let mut palette = [value0, value1, 0, 0, 0, 0, 0, 255];
+    if value0 > value1 {
+        for i in 1..7 {
+            palette[i + 1] = ((7 - i as u32) * value0 + i as u32 * value1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = ((5 - i as u32) * value0 + i as u32 * value1) / 5;
+        }
+    }
+
+    let mut index_bytes = [0; 8];
+    index_bytes[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(index_bytes);
+    std::array::from_fn(|i| palette[((indices >> (3 * i)) & 0b111) as usize] as u8)
+}
+
+/// The properties of a BC7 mode.
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_p_bits: bool,
+    shared_p_bits: bool,
+    index_bits: u32,
+    secondary_index_bits: u32,
+}
+
+#[rustfmt::skip]
+const BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode { subsets: 3, partition_bits: 4, rotation_bits: 0, index_selection_bits: 0, color_bits: 4, alpha_bits: 0, endpoint_p_bits: true, shared_p_bits: false, index_bits: 3, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 6, alpha_bits: 0, endpoint_p_bits: false, shared_p_bits: true, index_bits: 3, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 3, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 0, endpoint_p_bits: false, shared_p_bits: false, index_bits: 2, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 0, endpoint_p_bits: true, shared_p_bits: false, index_bits: 2, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 1, color_bits: 5, alpha_bits: 6, endpoint_p_bits: false, shared_p_bits: false, index_bits: 2, secondary_index_bits: 3 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 0, color_bits: 7, alpha_bits: 8, endpoint_p_bits: false, shared_p_bits: false, index_bits: 2, secondary_index_bits: 2 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 7, endpoint_p_bits: true, shared_p_bits: false, index_bits: 4, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 5, endpoint_p_bits: true, shared_p_bits: false, index_bits: 2, secondary_index_bits: 0 },
+];
+
+/// The subset of each pixel in the two-subset partitions.
+#[rustfmt::skip]
+const BC7_PARTITIONS_2: [[u8; 16]; 64] = [
+    [0,0,1,1,0,0,1,1,0,0,1,1,0,0,1,1], [0,0,0,1,0,0,0,1,0,0,0,1,0,0,0,1],
+    [0,1,1,1,0,1,1,1,0,1,1,1,0,1,1,1], [0,0,0,1,0,0,1,1,0,0,1,1,0,1,1,1],
+    [0,0,0,0,0,0,0,1,0,0,0,1,0,0,1,1], [0,0,1,1,0,1,1,1,0,1,1,1,1,1,1,1],
+    [0,0,0,1,0,0,1,1,0,1,1,1,1,1,1,1], [0,0,0,0,0,0,0,1,0,0,1,1,0,1,1,1],
+    [0,0,0,0,0,0,0,0,0,0,0,1,0,0,1,1], [0,0,1,1,0,1,1,1,1,1,1,1,1,1,1,1],
+    [0,0,0,0,0,0,0,1,0,1,1,1,1,1,1,1], [0,0,0,0,0,0,0,0,0,0,0,1,0,1,1,1],
+    [0,0,0,1,0,1,1,1,1,1,1,1,1,1,1,1], [0,0,0,0,0,0,0,0,1,1,1,1,1,1,1,1],
+    [0,0,0,0,1,1,1,1,1,1,1,1,1,1,1,1], [0,0,0,0,0,0,0,0,0,0,0,0,1,1,1,1],
+    [0,0,0,0,1,0,0,0,1,1,1,0,1,1,1,1], [0,1,1,1,0,0,0,1,0,0,0,0,0,0,0,0],
+    [0,0,0,0,0,0,0,0,1,0,0,0,1,1,1,0], [0,1,1,1,0,0,1,1,0,0,0,1,0,0,0,0],
+    [0,0,1,1,0,0,0,1,0,0,0,0,0,0,0,0], [0,0,0,0,1,0,0,0,1,1,0,0,1,1,1,0],
+    [0,0,0,0,0,0,0,0,1,0,0,0,1,1,0,0], [0,1,1,1,0,0,1,1,0,0,1,1,0,0,0,1],
+    [0,0,1,1,0,0,0,1,0,0,0,1,0,0,0,0], [0,0,0,0,1,0,0,0,1,0,0,0,1,1,0,0],
+    [0,1,1,0,0,1,1,0,0,1,1,0,0,1,1,0], [0,0,1,1,0,1,1,0,0,1,1,0,1,1,0,0],
+    [0,0,0,1,0,1,1,1,1,1,1,0,1,0,0,0], [0,0,0,0,1,1,1,1,1,1,1,1,0,0,0,0],
+    [0,1,1,1,0,0,0,1,1,0,0,0,1,1,1,0], [0,0,1,1,1,0,0,1,1,0,0,1,1,1,0,0],
+    [0,1,0,1,0,1,0,1,0,1,0,1,0,1,0,1], [0,0,0,0,1,1,1,1,0,0,0,0,1,1,1,1],
+    [0,1,0,1,1,0,1,0,0,1,0,1,1,0,1,0], [0,0,1,1,0,0,1,1,1,1,0,0,1,1,0,0],
+    [0,0,1,1,1,1,0,0,0,0,1,1,1,1,0,0], [0,1,0,1,0,1,0,1,1,0,1,0,1,0,1,0],
+    [0,1,1,0,1,0,0,1,0,1,1,0,1,0,0,1], [0,1,0,1,1,0,1,0,1,0,1,0,0,1,0,1],
+    [0,1,1,1,0,0,1,1,1,1,0,0,1,1,1,0], [0,0,0,1,0,0,1,1,1,1,0,0,1,0,0,0],
+    [0,0,1,1,0,0,1,0,0,1,0,0,1,1,0,0], [0,0,1,1,1,0,1,1,1,1,0,1,1,1,0,0],
+    [0,1,1,0,1,0,0,1,1,0,0,1,0,1,1,0], [0,0,1,1,1,1,0,0,1,1,0,0,0,0,1,1],
+    [0,1,1,0,0,1,1,0,1,0,0,1,1,0,0,1], [0,0,0,0,0,1,1,0,0,1,1,0,0,0,0,0],
+    [0,1,0,0,1,1,1,0,0,1,0,0,0,0,0,0], [0,0,1,0,0,1,1,1,0,0,1,0,0,0,0,0],
+    [0,0,0,0,0,0,1,0,0,1,1,1,0,0,1,0], [0,0,0,0,0,1,0,0,1,1,1,0,0,1,0,0],
+    [0,1,1,0,1,1,0,0,1,0,0,1,0,0,1,1], [0,0,1,1,0,1,1,0,1,1,0,0,1,0,0,1],
+    [0,1,1,0,0,0,1,1,1,0,0,1,1,1,0,0], [0,0,1,1,1,0,0,1,1,1,0,0,0,1,1,0],
+    [0,1,1,0,1,1,0,0,1,1,0,0,1,0,0,1], [0,1,1,0,0,0,1,1,0,0,1,1,1,0,0,1],
+    [0,1,1,1,1,1,1,0,1,0,0,0,0,0,0,1], [0,0,0,1,1,0,0,0,1,1,1,0,0,1,1,1],
+    [0,0,0,0,1,1,1,1,0,0,1,1,0,0,1,1], [0,0,1,1,0,0,1,1,1,1,1,1,0,0,0,0],
+    [0,0,1,0,0,0,1,0,1,1,1,0,1,1,1,0], [0,1,0,0,0,1,0,0,0,1,1,1,0,1,1,1],
+];
+
+/// The subset of each pixel in the three-subset partitions.
+#[rustfmt::skip]
+const BC7_PARTITIONS_3: [[u8; 16]; 64] = [
+    [0,0,1,1,0,0,1,1,0,2,2,1,2,2,2,2], [0,0,0,1,0,0,1,1,2,2,1,1,2,2,2,1],
+    [0,0,0,0,2,0,0,1,2,2,1,1,2,2,1,1], [0,2,2,2,0,0,2,2,0,0,1,1,0,1,1,1],
+    [0,0,0,0,0,0,0,0,1,1,2,2,1,1,2,2], [0,0,1,1,0,0,1,1,0,0,2,2,0,0,2,2],
+    [0,0,2,2,0,0,2,2,1,1,1,1,1,1,1,1], [0,0,1,1,0,0,1,1,2,2,1,1,2,2,1,1],
+    [0,0,0,0,0,0,0,0,1,1,1,1,2,2,2,2], [0,0,0,0,1,1,1,1,1,1,1,1,2,2,2,2],
+    [0,0,0,0,1,1,1,1,2,2,2,2,2,2,2,2], [0,0,1,2,0,0,1,2,0,0,1,2,0,0,1,2],
+    [0,1,1,2,0,1,1,2,0,1,1,2,0,1,1,2], [0,1,2,2,0,1,2,2,0,1,2,2,0,1,2,2],
+    [0,0,1,1,0,1,1,2,1,1,2,2,1,2,2,2], [0,0,1,1,2,0,0,1,2,2,0,0,2,2,2,0],
+    [0,0,0,1,0,0,1,1,0,1,1,2,1,1,2,2], [0,1,1,1,0,0,1,1,2,0,0,1,2,2,0,0],
+    [0,0,0,0,1,1,2,2,1,1,2,2,1,1,2,2], [0,0,2,2,0,0,2,2,0,0,2,2,1,1,1,1],
+    [0,1,1,1,0,1,1,1,0,2,2,2,0,2,2,2], [0,0,0,1,0,0,0,1,2,2,2,1,2,2,2,1],
+    [0,0,0,0,0,0,1,1,0,1,2,2,0,1,2,2], [0,0,0,0,1,1,0,0,2,2,1,0,2,2,1,0],
+    [0,1,2,2,0,1,2,2,0,0,1,1,0,0,0,0], [0,0,1,2,0,0,1,2,1,1,2,2,2,2,2,2],
+    [0,1,1,0,1,2,2,1,1,2,2,1,0,1,1,0], [0,0,0,0,0,1,1,0,1,2,2,1,1,2,2,1],
+    [0,0,2,2,1,1,0,2,1,1,0,2,0,0,2,2], [0,1,1,0,0,1,1,0,2,0,0,2,2,2,2,2],
+    [0,0,1,1,0,1,2,2,0,1,2,2,0,0,1,1], [0,0,0,0,2,0,0,0,2,2,1,1,2,2,2,1],
+    [0,0,0,0,0,0,0,2,1,1,2,2,1,2,2,2], [0,2,2,2,0,0,2,2,0,0,1,2,0,0,1,1],
+    [0,0,1,1,0,0,1,2,0,0,2,2,0,2,2,2], [0,1,2,0,0,1,2,0,0,1,2,0,0,1,2,0],
+    [0,0,0,0,1,1,1,1,2,2,2,2,0,0,0,0], [0,1,2,0,1,2,0,1,2,0,1,2,0,1,2,0],
+    [0,1,2,0,2,0,1,2,1,2,0,1,0,1,2,0], [0,0,1,1,2,2,0,0,1,1,2,2,0,0,1,1],
+    [0,0,1,1,1,1,2,2,2,2,0,0,0,0,1,1], [0,1,0,1,0,1,0,1,2,2,2,2,2,2,2,2],
+    [0,0,0,0,0,0,0,0,2,1,2,1,2,1,2,1], [0,0,2,2,1,1,2,2,0,0,2,2,1,1,2,2],
+    [0,0,2,2,0,0,1,1,0,0,2,2,0,0,1,1], [0,2,2,0,1,2,2,1,0,2,2,0,1,2,2,1],
+    [0,1,0,1,2,2,2,2,2,2,2,2,0,1,0,1], [0,0,0,0,2,1,2,1,2,1,2,1,2,1,2,1],
+    [0,1,0,1,0,1,0,1,0,1,0,1,2,2,2,2], [0,2,2,2,0,1,1,1,0,2,2,2,0,1,1,1],
+    [0,0,0,2,1,1,1,2,0,0,0,2,1,1,1,2], [0,0,0,0,2,1,1,2,2,1,1,2,2,1,1,2],
+    [0,2,2,2,0,1,1,1,0,1,1,1,0,2,2,2], [0,0,0,2,1,1,1,2,1,1,1,2,0,0,0,2],
+    [0,1,1,0,0,1,1,0,0,1,1,0,2,2,2,2], [0,0,0,0,0,0,0,0,2,1,1,2,2,1,1,2],
+    [0,1,1,0,0,1,1,0,2,2,2,2,2,2,2,2], [0,0,2,2,0,0,1,1,0,0,1,1,0,0,2,2],
+    [0,0,2,2,1,1,2,2,1,1,2,2,0,0,2,2], [0,0,0,0,0,0,0,0,0,0,0,0,2,1,1,2],
+    [0,0,0,2,0,0,0,1,0,0,0,2,0,0,0,1], [0,2,2,2,1,2,2,2,0,2,2,2,1,2,2,2],
+    [0,1,0,1,2,2,2,2,2,2,2,2,2,2,2,2], [0,1,1,1,2,0,1,1,2,2,0,1,2,2,2,0],
+];
+
+/// The anchor pixel of the second subset of the two-subset partitions.
+#[rustfmt::skip]
+const BC7_ANCHORS_2: [u8; 64] = [
+    15,15,15,15,15,15,15,15, 15,15,15,15,15,15,15,15,
+    15, 2, 8, 2, 2, 8, 8,15,  2, 8, 2, 2, 8, 8, 2, 2,
+    15,15, 6, 8, 2, 8,15,15,  2, 8, 2, 2, 2,15,15, 6,
+     6, 2, 6, 8,15,15, 2, 2, 15,15,15,15,15, 2, 2,15,
+];
+
+/// The anchor pixel of the second subset of the three-subset partitions.
+#[rustfmt::skip]
+const BC7_ANCHORS_3_SECOND: [u8; 64] = [
+     3, 3,15,15, 8, 3,15,15,  8, 8, 6, 6, 6, 5, 3, 3,
+     3, 3, 8,15, 3, 3, 6,10,  5, 8, 8, 6, 8, 5,15,15,
+     8,15, 3, 5, 6,10, 8,15, 15, 3,15, 5,15,15,15,15,
+     3,15, 5, 5, 5, 8, 5,10,  5,10, 8,13,15,12, 3, 3,
+];
+
+/// The anchor pixel of the third subset of the three-subset partitions.
+#[rustfmt::skip]
+const BC7_ANCHORS_3_THIRD: [u8; 64] = [
+    15, 8, 8, 3,15,15, 3, 8, 15,15,15,15,15,15,15, 8,
+    15, 8,15, 3,15, 8,15, 8,  3,15, 6,10,15,15,10, 8,
+    15, 3,15,10,10, 8, 9,10,  6,15, 8,15, 3, 6, 6, 8,
+    15, 3,15,15,15,15,15,15, 15,15,15,15, 3,15,15, 8,
+];
+
+const BC7_WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+const BC7_WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const BC7_WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// Reads the bits of a block from the least significant bit.
+struct BitReader {
+    bits: u128,
+    position: u32,
+}
+
+impl BitReader {
+    fn read(&mut self, count: u32) -> u32 {
+        let value = (self.bits >> self.position) as u32 & ((1u64 << count) - 1) as u32;
+        self.position += count;
+        value
+    }
+}
+
+fn bc7(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut reader = BitReader {
+        bits: u128::from_le_bytes(block.try_into().unwrap()),
+        position: 0,
+    };
+
+    // The mode is the number of zero bits before the first one bit
+    let mode_index = (reader.bits as u8).trailing_zeros() as usize;
+    let Some(mode) = BC7_MODES.get(mode_index) else {
+        return [[0; 4]; 16];
+    };
+    reader.position = mode_index as u32 + 1;
+
+    let partition = reader.read(mode.partition_bits) as usize;
+    let rotation = reader.read(mode.rotation_bits);
+    let index_selection = reader.read(mode.index_selection_bits);
+
+    // The endpoints of each subset as `[subset * 2 + endpoint][channel]`
+    let mut endpoints = [[0u32; 4]; 6];
+    let endpoint_count = mode.subsets * 2;
+    for channel in 0..3 {
+        for endpoint in &mut endpoints[..endpoint_count] {
+            endpoint[channel] = reader.read(mode.color_bits);
+        }
+    }
+    for endpoint in &mut endpoints[..endpoint_count] {
+        endpoint[3] = reader.read(mode.alpha_bits);
+    }
+
+    let mut color_bits = mode.color_bits;
+    let mut alpha_bits = mode.alpha_bits;
+    if mode.endpoint_p_bits || mode.shared_p_bits {
+        let p_bits: Vec<_> = if mode.endpoint_p_bits {
+            (0..endpoint_count).map(|_| reader.read(1)).collect()
+        } else {
+            (0..mode.subsets)
+                .flat_map(|_| {
+                    let p_bit = reader.read(1);
+                    [p_bit, p_bit]
+                })
+                .collect()
+        };
+        for (endpoint, p_bit) in endpoints.iter_mut().zip(p_bits) {
+            for value in endpoint.iter_mut() {
+                *value = (*value << 1) | p_bit;
+            }
+        }
+        color_bits += 1;
+        if alpha_bits > 0 {
+            alpha_bits += 1;
+        }
+    }
+
+    for endpoint in &mut endpoints[..endpoint_count] {
+        for value in &mut endpoint[..3] {
+            *value = expand_bits(*value, color_bits);
+        }
+        endpoint[3] = if alpha_bits > 0 {
+            expand_bits(endpoint[3], alpha_bits)
+        } else {
+            255
+        };
+    }
+
+    let subset_of = |pixel: usize| match mode.subsets {
+        2 => BC7_PARTITIONS_2[partition][pixel] as usize,
+        3 => BC7_PARTITIONS_3[partition][pixel] as usize,
+        _ => 0,
+    };
+    // Anchor pixels store their index with one bit less since its top bit is always 0
+    let is_anchor = |pixel: usize| {
+        pixel == 0
+            || match mode.subsets {
+                2 => pixel == BC7_ANCHORS_2[partition] as usize,
+                3 => {
+                    pixel == BC7_ANCHORS_3_SECOND[partition] as usize
+                        || pixel == BC7_ANCHORS_3_THIRD[partition] as usize
+                }
+                _ => false,
+            }
+    };
+
+    let primary_indices: [u32; 16] =
+        std::array::from_fn(|pixel| reader.read(mode.index_bits - is_anchor(pixel) as u32));
+    let secondary_indices: [u32; 16] = if mode.secondary_index_bits > 0 {
+        std::array::from_fn(|pixel| reader.read(mode.secondary_index_bits - (pixel == 0) as u32))
+    } else {
+        primary_indices
+    };
+
+    let weights = |bits| match bits {
+        2 => &BC7_WEIGHTS_2[..],
+        3 => &BC7_WEIGHTS_3[..],
+        _ => &BC7_WEIGHTS_4[..],
+    };
+    // Mode 4's index selection bit swaps which indices the color and alpha use
+    let ((color_indices, color_index_bits), (alpha_indices, alpha_index_bits)) =
+        if mode.secondary_index_bits == 0 {
+            (
+                (primary_indices, mode.index_bits),
+                (primary_indices, mode.index_bits),
+            )
+        } else if index_selection == 0 {
+            (
+                (primary_indices, mode.index_bits),
+                (secondary_indices, mode.secondary_index_bits),
+            )
+        } else {
+            (
+                (secondary_indices, mode.secondary_index_bits),
+                (primary_indices, mode.index_bits),
+            )
+        };
+
+    std::array::from_fn(|pixel| {
+        let subset = subset_of(pixel);
+        let (endpoint0, endpoint1) = (endpoints[subset * 2], endpoints[subset * 2 + 1]);
+        let color_weight = weights(color_index_bits)[color_indices[pixel] as usize];
+        let alpha_weight = weights(alpha_index_bits)[alpha_indices[pixel] as usize];
+        let interpolate = |channel: usize, weight: u32| {
+            (((64 - weight) * endpoint0[channel] + weight * endpoint1[channel] + 32) >> 6) as u8
+        };
+
+        let mut color = [
+            interpolate(0, color_weight),
+            interpolate(1, color_weight),
+            interpolate(2, color_weight),
+            interpolate(3, alpha_weight),
+        ];
+        if rotation > 0 {
+            color.swap(rotation as usize - 1, 3);
+        }
+        color
+    })
+}
+
+/// Expands a value with the given number of bits to 8 bits.
+fn expand_bits(value: u32, bits: u32) -> u32 {
+    let value = value << (8 - bits);
+    value | (value >> bits)
+}
+
+/// The ETC1 intensity modifiers by table and pixel index.
+const ETC1_MODIFIERS: [[i32; 4]; 8] = [
+    [2, 8, -2, -8],
+    [5, 17, -5, -17],
+    [9, 29, -9, -29],
+    [13, 42, -13, -42],
+    [18, 60, -18, -60],
+    [24, 80, -24, -80],
+    [33, 106, -33, -106],
+    [47, 183, -47, -183],
+];
+
+/// The distances of the ETC2 T and H modes.
+const ETC2_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+/// Decodes an ETC2 color block. With punch-through alpha the differential bit is the opaque bit
+/// and pixel index 2 is transparent in non-opaque blocks.
+fn etc2(block: &[u8], punch_through: bool) -> [[u8; 4]; 16] {
+    let bits = u64::from_be_bytes(block.try_into().unwrap());
+    let differential = bits & (1 << 33) != 0;
+    let opaque = !punch_through || differential;
+
+    // Pixels are stored in columns, the index bits are split into low and high halves
+    let pixel_index = |x: usize, y: usize| {
+        let bit = x * 4 + y;
+        let low = (bits >> bit) & 1;
+        let high = (bits >> (bit + 16)) & 1;
+        (high << 1 | low) as usize
+    };
+    let pixels_from_palette = |palette: [[i32; 3]; 4]| {
+        std::array::from_fn(|i| {
+            let (x, y) = (i % 4, i / 4);
+            let index = pixel_index(x, y);
+            if !opaque && index == 2 {
+                return [0, 0, 0, 0];
+            }
+
+            let [red, green, blue] = palette[index].map(|channel| channel.clamp(0, 255) as u8);
+            [red, green, blue, 255]
+        })
+    };
+    let byte = |i: usize| block[i] as u32;
+
+    if !differential && !punch_through {
+        // Individual mode with two 4-bit base colors
+        let base_colors = [
+            [byte(0) >> 4, byte(1) >> 4, byte(2) >> 4],
+            [byte(0) & 0xf, byte(1) & 0xf, byte(2) & 0xf],
+        ]
+        .map(|color| color.map(|channel| expand_bits(channel, 4) as i32));
+        return etc1_subblocks(bits, base_colors, opaque, pixel_index);
+    }
+
+    let red = (byte(0) >> 3) as i32 + sign_extend_3(byte(0) & 0x7);
+    let green = (byte(1) >> 3) as i32 + sign_extend_3(byte(1) & 0x7);
+    let blue = (byte(2) >> 3) as i32 + sign_extend_3(byte(2) & 0x7);
+
+    if !(0..32).contains(&red) {
+        // T mode
+        let base0 = [
+            ((byte(0) >> 3) & 0x3) << 2 | (byte(0) & 0x3),
+            byte(1) >> 4,
+            byte(1) & 0xf,
+        ]
+        .map(|channel| expand_bits(channel, 4) as i32);
+        let base1 = [byte(2) >> 4, byte(2) & 0xf, byte(3) >> 4]
+            .map(|channel| expand_bits(channel, 4) as i32);
+        let distance = ETC2_DISTANCES[(((byte(3) >> 1) & 0x6) | (byte(3) & 0x1)) as usize];
+
+        pixels_from_palette([
+            base0,
+            base1.map(|channel| channel + distance),
+            base1,
+            base1.map(|channel| channel - distance),
+        ])
+    } else if !(0..32).contains(&green) {
+        // H mode
+        let base0 = [
+            (byte(0) >> 3) & 0xf,
+            ((byte(0) & 0x7) << 1) | ((byte(1) >> 4) & 0x1),
+            (byte(1) & 0x8) | ((byte(1) & 0x3) << 1) | (byte(2) >> 7),
+        ]
+        .map(|channel| expand_bits(channel, 4) as i32);
+        let base1 = [
+            (byte(2) >> 3) & 0xf,
+            ((byte(2) & 0x7) << 1) | (byte(3) >> 7),
+            (byte(3) >> 3) & 0xf,
+        ]
+        .map(|channel| expand_bits(channel, 4) as i32);
+
+        let value = |color: [i32; 3]| (color[0] << 16) | (color[1] << 8) | color[2];
+        let distance_index =
+            (byte(3) & 0x4) | ((byte(3) << 1) & 0x2) | (value(base0) >= value(base1)) as u32;
+        let distance = ETC2_DISTANCES[distance_index as usize];
+
+        pixels_from_palette([
+            base0.map(|channel| channel + distance),
+            base0.map(|channel| channel - distance),
+            base1.map(|channel| channel + distance),
+            base1.map(|channel| channel - distance),
+        ])
+    } else if !(0..32).contains(&blue) {
+        // Planar mode with colors at the origin and the horizontal and vertical ends
+        let origin = [
+            expand_bits((byte(0) >> 1) & 0x3f, 6),
+            expand_bits(((byte(0) & 0x1) << 6) | ((byte(1) >> 1) & 0x3f), 7),
+            expand_bits(
+                ((byte(1) & 0x1) << 5) | (byte(2) & 0x18) | ((byte(2) & 0x3) << 1) | (byte(3) >> 7),
+                6,
+            ),
+        ];
+        let horizontal = [
+            expand_bits(((byte(3) >> 1) & 0x3e) | (byte(3) & 0x1), 6),
+            expand_bits(byte(4) >> 1, 7),
+            expand_bits(((byte(4) & 0x1) << 5) | (byte(5) >> 3), 6),
+        ];
+        let vertical = [
+            expand_bits(((byte(5) & 0x7) << 3) | (byte(6) >> 5), 6),
+            expand_bits(((byte(6) & 0x1f) << 2) | (byte(7) >> 6), 7),
+            expand_bits(byte(7) & 0x3f, 6),
+        ];
+
+        std::array::from_fn(|i| {
+            let (x, y) = ((i % 4) as i32, (i / 4) as i32);
+            let channel = |c: usize| {
+                let (o, h, v) = (origin[c] as i32, horizontal[c] as i32, vertical[c] as i32);
+                ((x * (h - o) + y * (v - o) + 4 * o + 2) >> 2).clamp(0, 255) as u8
+            };
+            [channel(0), channel(1), channel(2), 255]
+        })
+    } else {
+        // Differential mode with a 5-bit base color and a 3-bit offset
+        let base_colors = [
+            [byte(0) >> 3, byte(1) >> 3, byte(2) >> 3],
+            [red as u32, green as u32, blue as u32],
+        ]
+        .map(|color| color.map(|channel| expand_bits(channel, 5) as i32));
+        etc1_subblocks(bits, base_colors, opaque, pixel_index)
+    }
+}
+
+/// Decodes the two subblocks of the ETC1 individual and differential modes.
+fn etc1_subblocks(
+    bits: u64,
+    base_colors: [[i32; 3]; 2],
+    opaque: bool,
+    pixel_index: impl Fn(usize, usize) -> usize,
+) -> [[u8; 4]; 16] {
+    let tables = [((bits >> 37) & 0x7) as usize, ((bits >> 34) & 0x7) as usize];
+    let flip = bits & (1 << 32) != 0;
+
+    std::array::from_fn(|i| {
+        let (x, y) = (i % 4, i / 4);
+        let subblock = if flip { y / 2 } else { x / 2 };
+        let index = pixel_index(x, y);
+        if !opaque && index == 2 {
+            return [0, 0, 0, 0];
+        }
+
+        // Non-opaque punch-through blocks have no small modifiers
+        let modifier = if !opaque && index == 0 {
+            0
+        } else {
+            ETC1_MODIFIERS[tables[subblock]][index]
+        };
+        let [red, green, blue] =
+            base_colors[subblock].map(|channel| (channel + modifier).clamp(0, 255) as u8);
+        [red, green, blue, 255]
+    })
+}
+
+fn sign_extend_3(value: u32) -> i32 {
+    ((value as i32) << 29) >> 29
+}
+
+/// The EAC alpha modifiers by table and pixel index.
+#[rustfmt::skip]
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+/// Decodes an EAC alpha block.
+fn eac(block: &[u8]) -> [u8; 16] {
+    let base = block[0] as i32;
+    let multiplier = (block[1] >> 4) as i32;
+    let table = EAC_MODIFIERS[(block[1] & 0xf) as usize];
+    let bits = u64::from_be_bytes(block.try_into().unwrap());
+
+    // Pixels are stored in columns
+    std::array::from_fn(|i| {
+        let (x, y) = (i % 4, i / 4);
+        let index = (bits >> (45 - 3 * (x * 4 + y))) & 0x7;
+        (base + table[index as usize] * multiplier).clamp(0, 255) as u8
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Repeats a row of pixels for each row of a block.
+    fn rows(row: [[u8; 4]; 4]) -> [[u8; 4]; 16] {
+        std::array::from_fn(|i| row[i % 4])
+    }
+
+    #[test]
+    fn bc1_four_colors() {
+        // Red and blue endpoints with the indices 0, 1, 2, 3 in each row
+        let block = [0x00, 0xf8, 0x1f, 0x00, 0xe4, 0xe4, 0xe4, 0xe4];
+        let expected = rows([
+            [255, 0, 0, 255],
+            [0, 0, 255, 255],
+            [170, 0, 85, 255],
+            [85, 0, 170, 255],
+        ]);
+        assert_eq!(decode_block(TextureFormat::Bc1RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc1_three_colors_and_transparent() {
+        // The first endpoint isn't larger, so index 2 is the average and index 3 is transparent
+        let block = [0x1f, 0x00, 0x00, 0xf8, 0xe4, 0xe4, 0xe4, 0xe4];
+        let expected = rows([
+            [0, 0, 255, 255],
+            [255, 0, 0, 255],
+            [127, 0, 127, 255],
+            [0, 0, 0, 0],
+        ]);
+        assert_eq!(decode_block(TextureFormat::Bc1RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc2_explicit_alpha() {
+        // The color block always uses four colors and every pixel uses index 2
+        let block = [
+            0x10, 0x32, 0x54, 0x76, 0x98, 0xba, 0xdc, 0xfe, 0x1f, 0x00, 0x00, 0xf8, 0xaa, 0xaa,
+            0xaa, 0xaa,
+        ];
+        let expected: [[u8; 4]; 16] = std::array::from_fn(|i| [85, 0, 170, i as u8 * 17]);
+        assert_eq!(decode_block(TextureFormat::Bc2RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc3_eight_interpolated_alphas() {
+        // Every pixel uses the red endpoint, the alpha indices count from 0 to 7 twice
+        let block = [
+            255, 0, 136, 198, 250, 136, 198, 250, 0x00, 0xf8, 0x00, 0x00, 0, 0, 0, 0,
+        ];
+        let alphas = [255, 0, 218, 182, 145, 109, 72, 36];
+        let expected: [[u8; 4]; 16] = std::array::from_fn(|i| [255, 0, 0, alphas[i % 8]]);
+        assert_eq!(decode_block(TextureFormat::Bc3RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc4_six_interpolated_values() {
+        // The first endpoint isn't larger, so indices 6 and 7 are 0 and 255
+        let block = [0, 255, 136, 198, 250, 136, 198, 250];
+        let values = [0, 255, 51, 102, 153, 204, 0, 255];
+        let expected: [[u8; 4]; 16] = std::array::from_fn(|i| [values[i % 8], 0, 0, 255]);
+        assert_eq!(decode_block(TextureFormat::Bc4RUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc5_two_channels() {
+        // Red uses index 2 of eight values and green uses index 7 of six values
+        let block = [
+            200, 100, 146, 36, 73, 146, 36, 73, 10, 20, 255, 255, 255, 255, 255, 255,
+        ];
+        assert_eq!(
+            decode_block(TextureFormat::Bc5RgUnorm, &block),
+            [[185, 255, 0, 255]; 16]
+        );
+    }
+
+    #[test]
+    fn bc7_mode_0() {
+        // Three subsets with partition 0
+        let block = [
+            161, 112, 61, 136, 78, 27, 102, 44, 249, 165, 202, 163, 58, 227, 161, 58,
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            [96, 80, 63, 255], [126, 109, 93, 255], [207, 190, 174, 255], [189, 173, 156, 255],
+            [120, 103, 87, 255], [102, 86, 69, 255], [231, 214, 198, 255], [213, 197, 180, 255],
+            [96, 80, 63, 255], [60, 43, 64, 255], [42, 25, 161, 255], [189, 173, 156, 255],
+            [54, 37, 95, 255], [36, 20, 193, 255], [66, 49, 33, 255], [24, 8, 255, 255],
+        ];
+        assert_eq!(decode_block(TextureFormat::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc7_mode_1() {
+        // Two subsets with partition 13, which splits the block into rows
+        let block = [
+            54, 5, 186, 184, 212, 173, 245, 163, 145, 50, 229, 161, 58, 227, 161, 58,
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            [42, 102, 127, 255], [143, 203, 42, 255], [81, 141, 94, 255], [22, 82, 143, 255],
+            [123, 183, 59, 255], [62, 122, 110, 255], [163, 223, 26, 255], [104, 164, 75, 255],
+            [64, 124, 149, 255], [165, 225, 64, 255], [103, 163, 116, 255], [44, 104, 165, 255],
+            [145, 205, 81, 255], [84, 144, 132, 255], [185, 245, 48, 255], [44, 104, 165, 255],
+        ];
+        assert_eq!(decode_block(TextureFormat::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc7_mode_2() {
+        // Three subsets with partition 0
+        let block = [
+            4, 10, 90, 46, 82, 122, 117, 7, 99, 152, 196, 158, 236, 114, 114, 114,
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            [49, 173, 32, 255], [58, 181, 41, 255], [115, 239, 99, 255], [90, 214, 74, 255],
+            [49, 173, 32, 255], [58, 181, 41, 255], [115, 239, 99, 255], [90, 214, 74, 255],
+            [49, 173, 32, 255], [157, 16, 140, 255], [165, 24, 148, 255], [90, 214, 74, 255],
+            [148, 8, 131, 255], [157, 16, 140, 255], [165, 24, 148, 255], [140, 0, 123, 255],
+        ];
+        assert_eq!(decode_block(TextureFormat::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc7_mode_3() {
+        // Two subsets with partition 0, which splits the block into columns
+        let block = [
+            8, 20, 80, 75, 55, 229, 166, 237, 143, 140, 105, 70, 117, 114, 114, 114,
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            [34, 64, 94, 255], [57, 87, 117, 255], [220, 250, 24, 255], [151, 181, 211, 255],
+            [34, 64, 94, 255], [57, 87, 117, 255], [220, 250, 24, 255], [151, 181, 211, 255],
+            [34, 64, 94, 255], [57, 87, 117, 255], [220, 250, 24, 255], [151, 181, 211, 255],
+            [34, 64, 94, 255], [57, 87, 117, 255], [220, 250, 24, 255], [151, 181, 211, 255],
+        ];
+        assert_eq!(decode_block(TextureFormat::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc7_mode_4_with_index_selection_0() {
+        // Rotation 1 swaps alpha and red
+        let block = [
+            48, 5, 209, 59, 140, 113, 118, 114, 114, 114, 242, 80, 157, 241, 80, 157,
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            [43, 173, 32, 49], [139, 181, 41, 58], [81, 189, 49, 66], [24, 165, 24, 41],
+            [120, 173, 32, 49], [62, 181, 41, 58], [158, 189, 49, 66], [101, 165, 24, 41],
+            [43, 173, 32, 49], [139, 181, 41, 58], [81, 189, 49, 66], [24, 165, 24, 41],
+            [120, 173, 32, 49], [62, 181, 41, 58], [158, 189, 49, 66], [101, 165, 24, 41],
+        ];
+        assert_eq!(decode_block(TextureFormat::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc7_mode_4_with_index_selection_1() {
+        // Rotation 2 swaps alpha and green
+        let block = [
+            208, 5, 209, 59, 140, 113, 118, 114, 114, 114, 242, 80, 157, 241, 80, 157,
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            [45, 68, 28, 168], [62, 114, 45, 186], [52, 158, 35, 175], [41, 24, 24, 165],
+            [59, 68, 42, 182], [48, 114, 31, 172], [66, 158, 49, 189], [55, 24, 38, 179],
+            [45, 68, 28, 168], [62, 114, 45, 186], [52, 158, 35, 175], [41, 24, 24, 165],
+            [59, 68, 42, 182], [48, 114, 31, 172], [66, 158, 49, 189], [55, 24, 38, 179],
+        ];
+        assert_eq!(decode_block(TextureFormat::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc7_mode_5() {
+        // Rotation 3 swaps alpha and blue
+        let block = [
+            224, 5, 20, 229, 54, 50, 26, 156, 116, 114, 114, 114, 58, 57, 57, 57,
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            [33, 63, 17, 93], [57, 87, 28, 118], [80, 110, 39, 141], [10, 40, 6, 70],
+            [33, 63, 17, 93], [57, 87, 28, 118], [80, 110, 39, 141], [10, 40, 6, 70],
+            [33, 63, 17, 93], [57, 87, 28, 118], [80, 110, 39, 141], [10, 40, 6, 70],
+            [33, 63, 17, 93], [57, 87, 28, 118], [80, 110, 39, 141], [10, 40, 6, 70],
+        ];
+        assert_eq!(decode_block(TextureFormat::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc7_mode_6() {
+        let block = [
+            192, 2, 138, 114, 27, 25, 13, 167, 98, 11, 165, 79, 233, 131, 45, 199,
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            [15, 45, 75, 17], [39, 69, 99, 39], [62, 92, 122, 61], [11, 41, 71, 13],
+            [34, 64, 94, 34], [57, 87, 117, 57], [80, 110, 140, 78], [29, 59, 89, 30],
+            [52, 82, 112, 52], [76, 106, 136, 74], [25, 55, 85, 26], [48, 78, 108, 48],
+            [70, 100, 130, 69], [21, 51, 81, 22], [43, 73, 103, 43], [66, 96, 126, 65],
+        ];
+        assert_eq!(decode_block(TextureFormat::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc7_mode_7() {
+        // Two subsets with partition 13, which splits the block into rows
+        let block = [
+            128, 77, 65, 203, 209, 171, 251, 48, 137, 153, 131, 82, 117, 114, 114, 114,
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            [51, 173, 35, 53], [58, 179, 41, 55], [65, 186, 48, 56], [44, 166, 28, 52],
+            [51, 173, 35, 53], [58, 179, 41, 55], [65, 186, 48, 56], [44, 166, 28, 52],
+            [100, 222, 84, 70], [106, 228, 90, 72], [113, 235, 97, 73], [93, 215, 77, 69],
+            [100, 222, 84, 70], [106, 228, 90, 72], [113, 235, 97, 73], [93, 215, 77, 69],
+        ];
+        assert_eq!(decode_block(TextureFormat::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn etc2_individual_mode() {
+        // Side by side subblocks with 4-bit base colors
+        let block = [163, 92, 15, 84, 85, 170, 240, 240];
+        #[rustfmt::skip]
+        let expected = [
+            [179, 94, 9, 255], [199, 114, 29, 255], [27, 180, 231, 255], [0, 124, 175, 255],
+            [161, 76, 0, 255], [141, 56, 0, 255], [75, 228, 255, 255], [131, 255, 255, 255],
+            [179, 94, 9, 255], [199, 114, 29, 255], [27, 180, 231, 255], [0, 124, 175, 255],
+            [161, 76, 0, 255], [141, 56, 0, 255], [75, 228, 255, 255], [131, 255, 255, 255],
+        ];
+        assert_eq!(decode_block(TextureFormat::Etc2Rgb8Unorm, &block), expected);
+    }
+
+    #[test]
+    fn etc2_differential_mode() {
+        // Flipped subblocks with a 5-bit base color and an offset
+        let block = [165, 82, 248, 31, 85, 170, 240, 240];
+        #[rustfmt::skip]
+        let expected = [
+            [167, 84, 255, 255], [173, 90, 255, 255], [163, 80, 253, 255], [157, 74, 247, 255],
+            [163, 80, 253, 255], [157, 74, 247, 255], [167, 84, 255, 255], [173, 90, 255, 255],
+            [187, 146, 255, 255], [255, 255, 255, 255], [93, 52, 208, 255], [0, 0, 72, 255],
+            [93, 52, 208, 255], [0, 0, 72, 255], [187, 146, 255, 255], [255, 255, 255, 255],
+        ];
+        assert_eq!(decode_block(TextureFormat::Etc2Rgb8Unorm, &block), expected);
+    }
+
+    #[test]
+    fn etc2_t_mode() {
+        // The red offset overflows
+        let block = [243, 105, 45, 75, 85, 170, 240, 240];
+        #[rustfmt::skip]
+        let expected = [
+            [187, 102, 153, 255], [66, 253, 100, 255], [34, 221, 68, 255], [2, 189, 36, 255],
+            [34, 221, 68, 255], [2, 189, 36, 255], [187, 102, 153, 255], [66, 253, 100, 255],
+            [187, 102, 153, 255], [66, 253, 100, 255], [34, 221, 68, 255], [2, 189, 36, 255],
+            [34, 221, 68, 255], [2, 189, 36, 255], [187, 102, 153, 255], [66, 253, 100, 255],
+        ];
+        assert_eq!(decode_block(TextureFormat::Etc2Rgb8Unorm, &block), expected);
+    }
+
+    #[test]
+    fn etc2_h_mode() {
+        // The green offset overflows
+        let block = [66, 251, 29, 62, 85, 170, 240, 240];
+        #[rustfmt::skip]
+        let expected = [
+            [168, 117, 255, 255], [104, 53, 206, 255], [83, 202, 151, 255], [19, 138, 87, 255],
+            [83, 202, 151, 255], [19, 138, 87, 255], [168, 117, 255, 255], [104, 53, 206, 255],
+            [168, 117, 255, 255], [104, 53, 206, 255], [83, 202, 151, 255], [19, 138, 87, 255],
+            [83, 202, 151, 255], [19, 138, 87, 255], [168, 117, 255, 255], [104, 53, 206, 255],
+        ];
+        assert_eq!(decode_block(TextureFormat::Etc2Rgb8Unorm, &block), expected);
+    }
+
+    #[test]
+    fn etc2_planar_mode() {
+        // The blue offset overflows
+        let block = [91, 77, 250, 231, 53, 192, 252, 85];
+        #[rustfmt::skip]
+        let expected = [
+            [182, 205, 247, 255], [188, 167, 242, 255], [195, 129, 237, 255], [201, 90, 232, 255],
+            [144, 211, 207, 255], [150, 172, 202, 255], [156, 134, 197, 255], [162, 96, 192, 255],
+            [105, 216, 166, 255], [111, 178, 161, 255], [118, 140, 156, 255], [124, 101, 151, 255],
+            [67, 222, 126, 255], [73, 183, 121, 255], [79, 145, 116, 255], [85, 107, 111, 255],
+        ];
+        assert_eq!(decode_block(TextureFormat::Etc2Rgb8Unorm, &block), expected);
+    }
+
+    #[test]
+    fn etc2_punch_through_alpha() {
+        // The opaque bit is unset, so index 2 is transparent and index 0 has no modifier
+        let block = [165, 82, 248, 29, 85, 170, 240, 240];
+        #[rustfmt::skip]
+        let expected = [
+            [165, 82, 255, 255], [173, 90, 255, 255], [0, 0, 0, 0], [157, 74, 247, 255],
+            [0, 0, 0, 0], [157, 74, 247, 255], [165, 82, 255, 255], [173, 90, 255, 255],
+            [140, 99, 255, 255], [255, 255, 255, 255], [0, 0, 0, 0], [0, 0, 72, 255],
+            [0, 0, 0, 0], [0, 0, 72, 255], [140, 99, 255, 255], [255, 255, 255, 255],
+        ];
+        assert_eq!(
+            decode_block(TextureFormat::Etc2Rgb8A1Unorm, &block),
+            expected
+        );
+    }
+
+    #[test]
+    fn etc2_with_eac_alpha() {
+        // The alpha block has base 100, multiplier 3 and table 0, the color block is the one from
+        // `etc2_individual_mode`
+        let block = [
+            100, 48, 16, 71, 223, 203, 35, 77, 163, 92, 15, 84, 85, 170, 240, 240,
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            [179, 94, 9, 91], [199, 114, 29, 55], [27, 180, 231, 124], [0, 124, 175, 82],
+            [161, 76, 0, 106], [141, 56, 0, 142], [75, 228, 255, 73], [131, 255, 255, 115],
+            [179, 94, 9, 91], [199, 114, 29, 55], [27, 180, 231, 124], [0, 124, 175, 82],
+            [161, 76, 0, 106], [141, 56, 0, 142], [75, 228, 255, 73], [131, 255, 255, 115],
+        ];
+        assert_eq!(
+            decode_block(TextureFormat::Etc2Rgba8Unorm, &block),
+            expected
+        );
+    }
+
+    /// Creates a BC1 block with a single color.
+    fn solid_bc1(color: u16) -> [u8; 8] {
+        let [low, high] = color.to_le_bytes();
+        [low, high, 0, 0, 0, 0, 0, 0]
+    }
+
+    #[test]
+    fn decompress_crops_partial_blocks_of_mip_levels() {
+        const RED: u16 = 0xf800;
+        const GREEN: u16 = 0x07e0;
+        const BLUE: u16 = 0x001f;
+        const WHITE: u16 = 0xffff;
+        // 12x8 has 3x2 blocks, 6x4 has 2x1, and 3x2 and 1x1 have a single block each
+        let blocks = [RED, GREEN, BLUE, WHITE, RED, GREEN, BLUE, WHITE, GREEN, RED];
+        let texture = Texture::new(
+            (12, 8),
+            blocks.iter().flat_map(|&color| solid_bc1(color)).collect(),
+        )
+        .with_format(TextureFormat::Bc1RgbaSrgb)
+        .with_mip_level_count(4);
+
+        let decompressed = texture.decompress().unwrap();
+
+        assert_eq!(decompressed.format, TextureFormat::Rgba8Srgb);
+        assert_eq!(decompressed.size, (12, 8));
+        assert_eq!(decompressed.mip_level_count, 4);
+        decompressed.validate().unwrap();
+
+        let rgba = |color| match color {
+            RED => [255, 0, 0, 255],
+            GREEN => [0, 255, 0, 255],
+            BLUE => [0, 0, 255, 255],
+            _ => [255, 255, 255, 255],
+        };
+        let mut expected = Vec::new();
+        let mut first_block = 0;
+        for (width, height) in [(12, 8), (6, 4), (3, 2), (1, 1)] {
+            let blocks_wide = usize::div_ceil(width, 4);
+            for y in 0..height {
+                for x in 0..width {
+                    let block = first_block + y / 4 * blocks_wide + x / 4;
+                    expected.extend(rgba(blocks[block]));
+                }
+            }
+            first_block += blocks_wide * usize::div_ceil(height, 4);
+        }
+        assert_eq!(decompressed.data, expected);
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    fs::{self, File},
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, mpsc},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use bevy_ecs::prelude::*;
+use render::hot_reload::{HOT_RELOAD_INTERVAL, modified_time};
+
+use crate::prelude::{
+    DeltaTime, DenseStorage, DenseStorageIndex, Texture, TextureDataError, Textures, parse_dds,
+    parse_ktx2,
+};
+
+/// The load state of a texture loaded with `Textures::load`.
+#[derive(Debug)]
+pub enum LoadState {
+    /// The file is being decoded, the placeholder is drawn meanwhile.
+    Loading,
+    Loaded,
+    /// The file couldn't be loaded, the placeholder stays.
+    Failed(TextureLoadError),
+}
+
+#[derive(Debug)]
+pub enum TextureLoadError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file isn't a valid PNG.
+    Decode(png::DecodingError),
+    /// The KTX2 or DDS file is malformed.
+    InvalidContainer(&'static str),
+    /// The KTX2 or DDS file uses a format or layout that isn't supported.
+    UnsupportedFormat(String),
+    /// The texture data in the file doesn't match its size, format or mip levels.
+    InvalidData(TextureDataError),
+}
+
+impl fmt::Display for TextureLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureLoadError::Io(error) => write!(f, "failed to read file: {error}"),
+            TextureLoadError::Decode(error) => write!(f, "failed to decode PNG: {error}"),
+            TextureLoadError::InvalidContainer(reason) => write!(f, "invalid file: {reason}"),
+            TextureLoadError::UnsupportedFormat(format) => write!(f, "unsupported {format}"),
+            TextureLoadError::InvalidData(error) => write!(f, "invalid texture data: {error}"),
+        }
+    }
+}
+
+impl Error for TextureLoadError {}
+
+/// Loads a KTX2 or DDS file by its extension, other files are decoded as PNGs.
+pub fn load_texture(path: impl AsRef<Path>) -> Result<Texture, TextureLoadError> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_ascii_lowercase());
+
+    match extension.as_ref().and_then(|extension| extension.to_str()) {
+        Some("ktx2") => parse_ktx2(&fs::read(path).map_err(TextureLoadError::Io)?),
+        Some("dds") => parse_dds(&fs::read(path).map_err(TextureLoadError::Io)?),
+        _ => load_png(path),
+    }
+}
+
+/// Decodes a PNG file into an Rgba8 texture.
+fn load_png(path: &Path) -> Result<Texture, TextureLoadError> {
+    let file = File::open(path).map_err(TextureLoadError::Io)?;
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    // Expands palettes and low bit depths and strips 16-bit channels to 8 bits
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info().map_err(TextureLoadError::Decode)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(TextureLoadError::Decode)?;
+    buffer.truncate(info.buffer_size());
+
+    let data = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|gray_alpha| [gray_alpha[0], gray_alpha[0], gray_alpha[0], gray_alpha[1]])
+            .collect(),
+        // Palettes are expanded to Rgb or Rgba
+        png::ColorType::Grayscale | png::ColorType::Indexed => buffer
+            .iter()
+            .flat_map(|&gray| [gray, gray, gray, 255])
+            .collect(),
+    };
+
+    Ok(Texture::new((info.width, info.height), data))
+}
+
+/// The decoded textures or errors of finished loads and if they were reloads.
+type FinishedLoads = Arc<
+    Mutex<
+        Vec<(
+            DenseStorageIndex<Texture>,
+            bool,
+            Result<Texture, TextureLoadError>,
+        )>,
+    >,
+>;
+
+/// The maximum amount of worker threads that decode textures.
+const MAX_LOAD_THREADS: usize = 4;
+
+/// A file to decode into a texture and if it's a reload.
+type LoadJob = (DenseStorageIndex<Texture>, PathBuf, bool);
+
+/// Decodes textures on worker threads and tracks their load states.
+#[derive(Default)]
+pub(crate) struct TextureLoader {
+    load_states: HashMap<DenseStorageIndex<Texture>, LoadState>,
+    /// The files of loaded textures and their modification times when they were last loaded.
+    paths: HashMap<DenseStorageIndex<Texture>, (PathBuf, Option<SystemTime>)>,
+    workers: LoadWorkers,
+    /// Whether textures are reloaded when their files are modified.
+    pub(crate) hot_reload: bool,
+    /// The time since the files were last checked for modification.
+    since_poll: Duration,
+}
+
+impl TextureLoader {
+    /// Decodes the file on a worker thread into the texture.
+    pub(crate) fn load(&mut self, texture: DenseStorageIndex<Texture>, path: PathBuf) {
+        self.load_states.insert(texture, LoadState::Loading);
+        self.paths
+            .insert(texture, (path.clone(), modified_time(&path)));
+
+        self.workers.load((texture, path, false));
+    }
+
+    /// Gets the file a texture was loaded from.
+    pub(crate) fn get_path(&self, texture: DenseStorageIndex<Texture>) -> Option<&Path> {
+        self.paths.get(&texture).map(|(path, _)| path.as_path())
+    }
+
+    /// Finds a texture loaded from the file that still exists in `textures`.
+    pub(crate) fn find_path(
+        &self,
+        path: &Path,
+        textures: &DenseStorage<Texture>,
+    ) -> Option<DenseStorageIndex<Texture>> {
+        self.paths
+            .iter()
+            .find(|&(&texture, (texture_path, _))| {
+                texture_path == path && textures.contains(texture)
+            })
+            .map(|(&texture, _)| texture)
+    }
+
+    pub(crate) fn get_load_state(&self, texture: DenseStorageIndex<Texture>) -> Option<&LoadState> {
+        self.load_states.get(&texture)
+    }
+
+    /// Forgets the load state and file of a freed texture so it's no longer reloaded.
+    pub(crate) fn forget(&mut self, texture: DenseStorageIndex<Texture>) {
+        self.load_states.remove(&texture);
+        self.paths.remove(&texture);
+    }
+
+    /// Starts reloading the loaded textures whose files were modified, checking the files once
+    /// every `HOT_RELOAD_INTERVAL`.
+    fn reload_modified(&mut self, delta_time: Duration) {
+        self.since_poll += delta_time;
+        if self.since_poll < HOT_RELOAD_INTERVAL {
+            return;
+        }
+        self.since_poll = Duration::ZERO;
+
+        for (&texture, (path, modified)) in &mut self.paths {
+            if matches!(self.load_states.get(&texture), Some(LoadState::Loading)) {
+                continue;
+            }
+
+            let new_modified = modified_time(path);
+            if new_modified != *modified {
+                *modified = new_modified;
+                self.workers.load((texture, path.clone(), true));
+            }
+        }
+    }
+
+    /// Takes the textures that finished decoding, updating their load states.
+    fn take_finished(&mut self) -> Vec<(DenseStorageIndex<Texture>, Texture)> {
+        let finished = std::mem::take(
+            &mut *self
+                .workers
+                .finished
+                .lock()
+                .unwrap_or_else(|error| error.into_inner()),
+        );
+
+        finished
+            .into_iter()
+            .filter_map(|(texture, reload, result)| match result {
+                // The texture was freed while it was loading
+                _ if !self.load_states.contains_key(&texture) => None,
+                Ok(data) => {
+                    self.load_states.insert(texture, LoadState::Loaded);
+                    Some((texture, data))
+                }
+                // Failed reloads keep the last texture that loaded
+                Err(_) if reload => None,
+                Err(error) => {
+                    self.load_states.insert(texture, LoadState::Failed(error));
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// A pool of worker threads that decode the files sent to them.
+#[derive(Default)]
+struct LoadWorkers {
+    /// Sends files to the workers, which are started by the first load.
+    jobs: Option<mpsc::Sender<LoadJob>>,
+    /// The textures that finished decoding since the last update.
+    finished: FinishedLoads,
+}
+
+impl LoadWorkers {
+    /// Sends the file to a worker that adds the result to `finished`.
+    fn load(&mut self, job: LoadJob) {
+        let jobs = self
+            .jobs
+            .get_or_insert_with(|| start_workers(&self.finished));
+        // Start new workers if they stopped
+        if let Err(mpsc::SendError(job)) = jobs.send(job) {
+            let jobs = self.jobs.insert(start_workers(&self.finished));
+            let _ = jobs.send(job);
+        }
+    }
+}
+
+/// Starts the worker threads, which stop when the returned sender is dropped.
+fn start_workers(finished: &FinishedLoads) -> mpsc::Sender<LoadJob> {
+    let (sender, receiver) = mpsc::channel::<LoadJob>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    let worker_count = thread::available_parallelism()
+        .map_or(1, |count| count.get())
+        .min(MAX_LOAD_THREADS);
+
+    for _ in 0..worker_count {
+        let receiver = receiver.clone();
+        let finished = finished.clone();
+        thread::spawn(move || {
+            loop {
+                // The receiver is only locked while waiting so the others can decode meanwhile
+                let job = receiver
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner())
+                    .recv();
+                let Ok((texture, path, reload)) = job else {
+                    break;
+                };
+
+                let result = load_texture(&path);
+                finished
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner())
+                    .push((texture, reload, result));
+            }
+        });
+    }
+
+    sender
+}
+
+/// Starts reloading the textures whose files were modified if hot-reloading is enabled.
+pub(crate) fn reload_modified_textures(delta_time: Res<DeltaTime>, mut textures: ResMut<Textures>) {
+    let loader = &mut textures.bypass_change_detection().loader;
+    if loader.hot_reload {
+        loader.reload_modified(delta_time.0);
+    }
+}
+
+/// Swaps in the textures that finished loading, keeping their indices.
+pub(crate) fn finish_texture_loads(mut textures: ResMut<Textures>) {
+    let textures = textures.bypass_change_detection();
+    let finished = textures.loader.take_finished();
+
+    for (index, texture) in finished {
+        // The texture may have been freed while it was loading
+        if let Some(old_texture) = textures.textures.get_mut(index) {
+            *old_texture = texture;
+            textures.modified.push(index);
+        }
+    }
+}
@@ -1,22 +1,435 @@
+use std::{
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+};
+
 use bevy_ecs::prelude::*;
+use render::wgpu;
+
+use crate::{
+    prelude::{DenseStorage, DenseStorageIndex, LoadState, StrongHandle},
+    texture_decompression,
+    texture_loader::TextureLoader,
+};
+
+/// The pixel format of a texture's data.
+///
+/// The block-compressed formats store 4x4 pixel blocks. They're decompressed on the CPU when the
+/// GPU doesn't support them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextureFormat {
+    /// 8-bit sRGB color with linear alpha.
+    #[default]
+    Rgba8Srgb,
+    /// 8-bit linear RGBA for data textures such as normal maps.
+    Rgba8Unorm,
+    /// A single 8-bit channel for masks. (Sampled as `(r, 0, 0, 1)`)
+    R8,
+    /// Two 8-bit channels. (Sampled as `(r, g, 0, 1)`)
+    Rg8,
+    /// 16-bit float RGBA for HDR colors. (Little-endian `f16` bytes)
+    Rgba16Float,
+    /// BC1 (DXT1) with 1-bit alpha.
+    Bc1RgbaSrgb,
+    Bc1RgbaUnorm,
+    /// BC2 (DXT3) with 4-bit alpha.
+    Bc2RgbaSrgb,
+    Bc2RgbaUnorm,
+    /// BC3 (DXT5) with interpolated alpha.
+    Bc3RgbaSrgb,
+    Bc3RgbaUnorm,
+    /// BC4 with a single channel.
+    Bc4RUnorm,
+    /// BC5 with two channels, usually for normal maps.
+    Bc5RgUnorm,
+    /// BC7 with high quality color and alpha.
+    Bc7RgbaSrgb,
+    Bc7RgbaUnorm,
+    /// ETC2 without alpha.
+    Etc2Rgb8Srgb,
+    Etc2Rgb8Unorm,
+    /// ETC2 with 1-bit alpha.
+    Etc2Rgb8A1Srgb,
+    Etc2Rgb8A1Unorm,
+    /// ETC2 with EAC alpha.
+    Etc2Rgba8Srgb,
+    Etc2Rgba8Unorm,
+}
+
+impl TextureFormat {
+    /// Gets the width and height of a block in pixels and its size in bytes. (Uncompressed formats
+    /// have single pixel blocks)
+    pub fn block_size(self) -> (u32, u32) {
+        match self {
+            TextureFormat::Rgba8Srgb | TextureFormat::Rgba8Unorm => (1, 4),
+            TextureFormat::R8 => (1, 1),
+            TextureFormat::Rg8 => (1, 2),
+            TextureFormat::Rgba16Float => (1, 8),
+            TextureFormat::Bc1RgbaSrgb
+            | TextureFormat::Bc1RgbaUnorm
+            | TextureFormat::Bc4RUnorm
+            | TextureFormat::Etc2Rgb8Srgb
+            | TextureFormat::Etc2Rgb8Unorm
+            | TextureFormat::Etc2Rgb8A1Srgb
+            | TextureFormat::Etc2Rgb8A1Unorm => (4, 8),
+            TextureFormat::Bc2RgbaSrgb
+            | TextureFormat::Bc2RgbaUnorm
+            | TextureFormat::Bc3RgbaSrgb
+            | TextureFormat::Bc3RgbaUnorm
+            | TextureFormat::Bc5RgUnorm
+            | TextureFormat::Bc7RgbaSrgb
+            | TextureFormat::Bc7RgbaUnorm
+            | TextureFormat::Etc2Rgba8Srgb
+            | TextureFormat::Etc2Rgba8Unorm => (4, 16),
+        }
+    }
+
+    pub fn is_compressed(self) -> bool {
+        self.block_size().0 > 1
+    }
 
-use crate::prelude::DenseStorage;
+    /// Checks if the data isn't sRGB encoded.
+    pub fn is_linear(self) -> bool {
+        !matches!(
+            self,
+            TextureFormat::Rgba8Srgb
+                | TextureFormat::Bc1RgbaSrgb
+                | TextureFormat::Bc2RgbaSrgb
+                | TextureFormat::Bc3RgbaSrgb
+                | TextureFormat::Bc7RgbaSrgb
+                | TextureFormat::Etc2Rgb8Srgb
+                | TextureFormat::Etc2Rgb8A1Srgb
+                | TextureFormat::Etc2Rgba8Srgb
+        )
+    }
 
-/// Holds texture data (Rgba8).
+    /// Gets the features the GPU needs to sample the format directly.
+    pub fn required_features(self) -> wgpu::Features {
+        match self {
+            TextureFormat::Bc1RgbaSrgb
+            | TextureFormat::Bc1RgbaUnorm
+            | TextureFormat::Bc2RgbaSrgb
+            | TextureFormat::Bc2RgbaUnorm
+            | TextureFormat::Bc3RgbaSrgb
+            | TextureFormat::Bc3RgbaUnorm
+            | TextureFormat::Bc4RUnorm
+            | TextureFormat::Bc5RgUnorm
+            | TextureFormat::Bc7RgbaSrgb
+            | TextureFormat::Bc7RgbaUnorm => wgpu::Features::TEXTURE_COMPRESSION_BC,
+            TextureFormat::Etc2Rgb8Srgb
+            | TextureFormat::Etc2Rgb8Unorm
+            | TextureFormat::Etc2Rgb8A1Srgb
+            | TextureFormat::Etc2Rgb8A1Unorm
+            | TextureFormat::Etc2Rgba8Srgb
+            | TextureFormat::Etc2Rgba8Unorm => wgpu::Features::TEXTURE_COMPRESSION_ETC2,
+            TextureFormat::Rgba8Srgb
+            | TextureFormat::Rgba8Unorm
+            | TextureFormat::R8
+            | TextureFormat::Rg8
+            | TextureFormat::Rgba16Float => wgpu::Features::empty(),
+        }
+    }
+
+    /// Gets the uncompressed format the data is decompressed to on the CPU.
+    pub fn decompressed(self) -> TextureFormat {
+        match self {
+            TextureFormat::Bc4RUnorm => TextureFormat::R8,
+            TextureFormat::Bc5RgUnorm => TextureFormat::Rg8,
+            format if !format.is_compressed() => format,
+            format if format.is_linear() => TextureFormat::Rgba8Unorm,
+            _ => TextureFormat::Rgba8Srgb,
+        }
+    }
+
+    pub fn to_wgpu(self) -> wgpu::TextureFormat {
+        match self {
+            TextureFormat::Rgba8Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            TextureFormat::Rgba8Unorm => wgpu::TextureFormat::Rgba8Unorm,
+            TextureFormat::R8 => wgpu::TextureFormat::R8Unorm,
+            TextureFormat::Rg8 => wgpu::TextureFormat::Rg8Unorm,
+            TextureFormat::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+            TextureFormat::Bc1RgbaSrgb => wgpu::TextureFormat::Bc1RgbaUnormSrgb,
+            TextureFormat::Bc1RgbaUnorm => wgpu::TextureFormat::Bc1RgbaUnorm,
+            TextureFormat::Bc2RgbaSrgb => wgpu::TextureFormat::Bc2RgbaUnormSrgb,
+            TextureFormat::Bc2RgbaUnorm => wgpu::TextureFormat::Bc2RgbaUnorm,
+            TextureFormat::Bc3RgbaSrgb => wgpu::TextureFormat::Bc3RgbaUnormSrgb,
+            TextureFormat::Bc3RgbaUnorm => wgpu::TextureFormat::Bc3RgbaUnorm,
+            TextureFormat::Bc4RUnorm => wgpu::TextureFormat::Bc4RUnorm,
+            TextureFormat::Bc5RgUnorm => wgpu::TextureFormat::Bc5RgUnorm,
+            TextureFormat::Bc7RgbaSrgb => wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+            TextureFormat::Bc7RgbaUnorm => wgpu::TextureFormat::Bc7RgbaUnorm,
+            TextureFormat::Etc2Rgb8Srgb => wgpu::TextureFormat::Etc2Rgb8UnormSrgb,
+            TextureFormat::Etc2Rgb8Unorm => wgpu::TextureFormat::Etc2Rgb8Unorm,
+            TextureFormat::Etc2Rgb8A1Srgb => wgpu::TextureFormat::Etc2Rgb8A1UnormSrgb,
+            TextureFormat::Etc2Rgb8A1Unorm => wgpu::TextureFormat::Etc2Rgb8A1Unorm,
+            TextureFormat::Etc2Rgba8Srgb => wgpu::TextureFormat::Etc2Rgba8UnormSrgb,
+            TextureFormat::Etc2Rgba8Unorm => wgpu::TextureFormat::Etc2Rgba8Unorm,
+        }
+    }
+}
+
+/// Holds texture data. (Rows of pixels or blocks from the top without padding, followed by the
+/// smaller mip levels if there are any)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Texture {
     pub size: (u32, u32),
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub format: TextureFormat,
+    #[cfg_attr(feature = "serde", serde(default = "default_mip_level_count"))]
+    pub mip_level_count: u32,
     // Make this an `Option<Vec<u8>>` in the future to allow unloading from the cpu side
     pub data: Vec<u8>,
 }
 
+#[cfg(feature = "serde")]
+fn default_mip_level_count() -> u32 {
+    1
+}
+
+/// Where a mip level is in a texture's data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MipLevelLayout {
+    pub offset: usize,
+    pub bytes_per_row: u32,
+    /// The number of rows of pixels or blocks.
+    pub rows: u32,
+    /// The size rounded up to whole blocks.
+    pub physical_size: (u32, u32),
+}
+
+impl MipLevelLayout {
+    pub fn len(&self) -> usize {
+        self.bytes_per_row as usize * self.rows as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Texture {
+    /// Creates an `Rgba8Srgb` texture.
+    pub fn new(size: (u32, u32), data: Vec<u8>) -> Self {
+        Self {
+            size,
+            format: TextureFormat::Rgba8Srgb,
+            mip_level_count: 1,
+            data,
+        }
+    }
+
+    pub fn with_format(mut self, format: TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the number of mip levels in the data.
+    pub fn with_mip_level_count(mut self, mip_level_count: u32) -> Self {
+        self.mip_level_count = mip_level_count;
+        self
+    }
+
+    /// Creates the gray checkerboard drawn while a texture is loading.
+    pub fn placeholder() -> Self {
+        #[rustfmt::skip]
+        let data = vec![
+            96, 96, 96, 255, 160, 160, 160, 255,
+            160, 160, 160, 255, 96, 96, 96, 255,
+        ];
+        Self::new((2, 2), data)
+    }
+
+    /// Creates the magenta and black checkerboard drawn instead of invalid textures.
+    pub fn fallback() -> Self {
+        #[rustfmt::skip]
+        let data = vec![
+            255, 0, 255, 255, 0, 0, 0, 255,
+            0, 0, 0, 255, 255, 0, 255, 255,
+        ];
+        Self::new((2, 2), data)
+    }
+
+    /// Gets the size of a mip level in pixels.
+    pub fn mip_level_size(&self, level: u32) -> (u32, u32) {
+        let shrink = |dimension: u32| dimension.checked_shr(level).unwrap_or(0).max(1);
+        (shrink(self.size.0), shrink(self.size.1))
+    }
+
+    /// Gets where each mip level is in the data or `None` if it overflows.
+    pub fn mip_level_layouts(&self) -> Option<Vec<MipLevelLayout>> {
+        let (block_dimension, block_bytes) = self.format.block_size();
+        let mut offset = 0usize;
+
+        (0..self.mip_level_count)
+            .map(|level| {
+                let (width, height) = self.mip_level_size(level);
+                let (blocks_wide, rows) = (
+                    width.div_ceil(block_dimension),
+                    height.div_ceil(block_dimension),
+                );
+
+                let layout = MipLevelLayout {
+                    offset,
+                    bytes_per_row: blocks_wide.checked_mul(block_bytes)?,
+                    rows,
+                    physical_size: (
+                        blocks_wide.checked_mul(block_dimension)?,
+                        rows.checked_mul(block_dimension)?,
+                    ),
+                };
+                offset = (layout.bytes_per_row as usize)
+                    .checked_mul(rows as usize)
+                    .and_then(|len| offset.checked_add(len))?;
+                Some(layout)
+            })
+            .collect()
+    }
+
+    /// Checks that the texture isn't empty, compressed sizes are whole blocks, the mip level count
+    /// fits the size and the data length matches.
+    pub fn validate(&self) -> Result<(), TextureDataError> {
+        self.validate_header()?;
+
+        let expected = self
+            .mip_level_layouts()
+            .and_then(|layouts| layouts.last().map(|layout| layout.offset + layout.len()))
+            .ok_or(TextureDataError::TooLarge)?;
+        if self.data.len() != expected {
+            return Err(TextureDataError::WrongDataLength {
+                expected,
+                actual: self.data.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks everything `validate` does except the data length. (Run before computing the mip
+    /// level layouts of untrusted sizes)
+    pub fn validate_header(&self) -> Result<(), TextureDataError> {
+        if self.size.0 == 0 || self.size.1 == 0 {
+            return Err(TextureDataError::EmptySize);
+        }
+
+        let block_dimension = self.format.block_size().0;
+        if !self.size.0.is_multiple_of(block_dimension)
+            || !self.size.1.is_multiple_of(block_dimension)
+        {
+            return Err(TextureDataError::PartialBlocks);
+        }
+
+        let max_mip_level_count = u32::BITS - self.size.0.max(self.size.1).leading_zeros();
+        if self.mip_level_count == 0 || self.mip_level_count > max_mip_level_count {
+            return Err(TextureDataError::WrongMipLevelCount {
+                max: max_mip_level_count,
+                actual: self.mip_level_count,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the size fits the largest texture dimension the GPU supports.
+    pub fn validate_size_limit(&self, max_dimension: u32) -> Result<(), TextureDataError> {
+        if self.size.0 > max_dimension || self.size.1 > max_dimension {
+            return Err(TextureDataError::AboveSizeLimit {
+                size: self.size,
+                max_dimension,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Decompresses a block-compressed texture into its uncompressed format, keeping the mip
+    /// levels. (Uncompressed textures are copied)
+    pub fn decompress(&self) -> Result<Texture, TextureDataError> {
+        self.validate()?;
+
+        if self.format.is_compressed() {
+            Ok(texture_decompression::decompress(self))
+        } else {
+            Ok(Texture {
+                size: self.size,
+                format: self.format,
+                mip_level_count: self.mip_level_count,
+                data: self.data.clone(),
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureDataError {
+    /// The width or height is 0.
+    EmptySize,
+    /// The size of a compressed texture isn't a multiple of its block size.
+    PartialBlocks,
+    /// The mip level count is 0 or more than the size allows.
+    WrongMipLevelCount { max: u32, actual: u32 },
+    /// The row pitch or data length overflows.
+    TooLarge,
+    /// The width or height is larger than the GPU supports.
+    AboveSizeLimit {
+        size: (u32, u32),
+        max_dimension: u32,
+    },
+    /// The data length doesn't match the size, format and mip levels.
+    WrongDataLength { expected: usize, actual: usize },
+}
+
+impl fmt::Display for TextureDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureDataError::EmptySize => write!(f, "texture size is empty"),
+            TextureDataError::PartialBlocks => {
+                write!(
+                    f,
+                    "compressed texture size isn't a multiple of its block size"
+                )
+            }
+            TextureDataError::WrongMipLevelCount { max, actual } => {
+                write!(f, "expected 1 to {max} mip levels, got {actual}")
+            }
+            TextureDataError::TooLarge => write!(f, "texture is too large"),
+            TextureDataError::AboveSizeLimit {
+                size,
+                max_dimension,
+            } => write!(
+                f,
+                "texture size {}x{} is above the GPU limit of {max_dimension}",
+                size.0, size.1
+            ),
+            TextureDataError::WrongDataLength { expected, actual } => {
+                write!(f, "expected {expected} bytes of texture data, got {actual}")
+            }
+        }
+    }
+}
+
+impl Error for TextureDataError {}
+
+/// Sent when a texture can't be uploaded, the fallback checkerboard is drawn instead.
+#[derive(Clone, Event)]
+pub struct TextureError {
+    pub texture: DenseStorageIndex<Texture>,
+    pub error: TextureDataError,
+}
+
 /// Holds sampler data.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sampler;
 
 /// Holds textures and samplers.
-#[derive(Resource)]
+#[derive(Default, Resource)]
 pub struct Textures {
     pub(crate) textures: DenseStorage<Texture>,
     pub(crate) samplers: DenseStorage<Sampler>,
+    pub(crate) loader: TextureLoader,
+    /// The textures whose data was replaced, re-uploaded on their own if their size didn't change.
+    pub(crate) modified: Vec<DenseStorageIndex<Texture>>,
     pub(crate) changed: bool,
 }
 
@@ -38,14 +451,47 @@ impl Textures {
         self.changed = true;
         &mut self.samplers
     }
-}
 
-impl Default for Textures {
-    fn default() -> Self {
-        Self {
-            textures: DenseStorage::new(),
-            samplers: DenseStorage::new(),
-            changed: false,
+    /// Loads a PNG, KTX2 or DDS file on a worker thread. The texture is a placeholder until it's loaded.
+    pub fn load(&mut self, path: impl Into<PathBuf>) -> StrongHandle<Texture> {
+        self.changed = true;
+        let handle = self.textures.push_strong(Texture::placeholder());
+        self.loader.load(handle.index(), path.into());
+
+        handle
+    }
+
+    /// Gets a strong handle to the texture loaded from the file or starts loading it if there's
+    /// none.
+    pub fn get_or_load(&mut self, path: impl AsRef<Path>) -> StrongHandle<Texture> {
+        let path = path.as_ref();
+        self.loader
+            .find_path(path, &self.textures)
+            .and_then(|texture| self.textures.upgrade(texture))
+            .unwrap_or_else(|| self.load(path))
+    }
+
+    /// Gets the file a texture was loaded from with `load`.
+    pub fn get_path(&self, texture: DenseStorageIndex<Texture>) -> Option<&Path> {
+        self.loader.get_path(texture)
+    }
+
+    /// Gets the load state of a texture or `None` if it wasn't loaded with `load`.
+    pub fn get_load_state(&self, texture: DenseStorageIndex<Texture>) -> Option<&LoadState> {
+        self.loader.get_load_state(texture)
+    }
+
+    /// Frees the textures and samplers pushed with `push_strong` that have no strong handles
+    /// left. (Their GPU textures are dropped when the textures are rebuilt next frame)
+    pub(crate) fn free_unused(&mut self) {
+        let freed_textures = self.textures.remove_unused();
+        for &(index, _) in &freed_textures {
+            self.loader.forget(index);
         }
+
+        let textures_freed = !freed_textures.is_empty();
+        let samplers_freed = !self.samplers.remove_unused().is_empty();
+
+        self.changed |= textures_freed || samplers_freed;
     }
 }
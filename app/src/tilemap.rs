@@ -0,0 +1,246 @@
+use bevy_ecs::prelude::*;
+use render::{
+    glam::{Mat4, Quat, UVec2, Vec2},
+    prelude::Instance,
+};
+
+/// The width and height of a tilemap chunk in tiles.
+pub const TILEMAP_CHUNK_SIZE: u32 = 16;
+
+/// Draws a grid of tiles from the tileset texture of the entity's `Material`. Tile `(0, 0)` is the
+/// bottom left tile and its bottom left corner is at the origin.
+///
+/// The tiles are drawn in chunks and a chunk's instances are only rebuilt when its tiles change.
+/// Changing tiles only rewrites the changed chunks on the GPU unless it changes how many tiles a
+/// chunk draws.
+#[derive(Clone, Component)]
+pub struct Tilemap {
+    /// The size of the map in tiles.
+    size: UVec2,
+    /// The size of a tile in world units.
+    tile_size: Vec2,
+    /// The size of the tileset texture in tiles.
+    tileset_size: UVec2,
+    /// The tile indices into the tileset, row by row from the bottom.
+    tiles: Vec<Option<u32>>,
+    /// The cached instances of each chunk, row by row from the bottom.
+    chunks: Vec<TilemapChunk>,
+    /// The transform, texture, sampler and normal map the chunk instances were built with.
+    built_with: Option<(Mat4, u32, u32, u32)>,
+}
+
+impl Tilemap {
+    /// Creates a new empty `Tilemap`. `tileset_size` is the amount of tile columns and rows in the
+    /// tileset texture, tiles are indexed left to right and top to bottom.
+    pub fn new(size: UVec2, tile_size: Vec2, tileset_size: UVec2) -> Self {
+        let chunk_count = UVec2::new(
+            size.x.div_ceil(TILEMAP_CHUNK_SIZE),
+            size.y.div_ceil(TILEMAP_CHUNK_SIZE),
+        );
+
+        Self {
+            size,
+            tile_size,
+            tileset_size: tileset_size.max(UVec2::ONE),
+            tiles: vec![None; size.x as usize * size.y as usize],
+            chunks: vec![TilemapChunk::default(); chunk_count.x as usize * chunk_count.y as usize],
+            built_with: None,
+        }
+    }
+
+    /// Gets the size of the map in tiles.
+    pub fn get_size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// Gets the size of a tile in world units.
+    pub fn get_tile_size(&self) -> Vec2 {
+        self.tile_size
+    }
+
+    /// Gets the tile at the position or `None` if it's empty or outside the map.
+    pub fn get(&self, position: UVec2) -> Option<u32> {
+        self.tile_index(position).and_then(|i| self.tiles[i])
+    }
+
+    /// Sets the tile at the position. (`None` clears the tile) Returns `false` if the position is
+    /// outside the map.
+    pub fn set(&mut self, position: UVec2, tile: Option<u32>) -> bool {
+        let Some(i) = self.tile_index(position) else {
+            return false;
+        };
+
+        if self.tiles[i] != tile {
+            self.tiles[i] = tile;
+            self.mark_chunk_dirty(position);
+        }
+
+        true
+    }
+
+    /// Sets every tile in the rectangle from `min` to `max` (inclusive), clamped to the map.
+    pub fn fill(&mut self, min: UVec2, max: UVec2, tile: Option<u32>) {
+        if self.size.cmpeq(UVec2::ZERO).any() {
+            return;
+        }
+
+        let max = max.min(self.size - UVec2::ONE);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                self.set(UVec2::new(x, y), tile);
+            }
+        }
+    }
+
+    /// Clears every tile.
+    pub fn clear(&mut self) {
+        self.fill(UVec2::ZERO, self.size, None);
+    }
+
+    fn tile_index(&self, position: UVec2) -> Option<usize> {
+        (position.x < self.size.x && position.y < self.size.y)
+            .then(|| position.y as usize * self.size.x as usize + position.x as usize)
+    }
+
+    fn chunk_columns(&self) -> u32 {
+        self.size.x.div_ceil(TILEMAP_CHUNK_SIZE)
+    }
+
+    fn mark_chunk_dirty(&mut self, position: UVec2) {
+        let chunk = position / TILEMAP_CHUNK_SIZE;
+        let i = chunk.y as usize * self.chunk_columns() as usize + chunk.x as usize;
+        self.chunks[i].dirty = true;
+    }
+
+    /// Rebuilds the instances of the dirty chunks, or every chunk if the transform, texture,
+    /// sampler or normal map changed.
+    pub(crate) fn update_chunks(
+        &mut self,
+        matrix: Mat4,
+        texture: u32,
+        sampler: u32,
+        normal_texture: u32,
+    ) {
+        let built_with = (matrix, texture, sampler, normal_texture);
+        let rebuild_all = self.built_with != Some(built_with);
+        self.built_with = Some(built_with);
+
+        let chunk_columns = self.chunk_columns();
+        for i in 0..self.chunks.len() {
+            if rebuild_all || self.chunks[i].dirty {
+                let chunk = UVec2::new(i as u32 % chunk_columns, i as u32 / chunk_columns);
+                self.chunks[i] = TilemapChunk {
+                    dirty: false,
+                    instances: self.build_chunk(chunk, built_with),
+                    instance_offset: None,
+                };
+            }
+        }
+    }
+
+    /// Rebuilds the instances of the dirty chunks with the transform, texture, sampler and normal
+    /// map they were last built with. Returns the rebuilt chunks as writes to the instance buffer,
+    /// or `None` if a chunk's instance count changed and the instance buffer needs to be rebuilt.
+    pub(crate) fn update_dirty_chunks(&mut self) -> Option<Vec<(u32, Vec<Instance>)>> {
+        let built_with = self.built_with?;
+        let chunk_columns = self.chunk_columns();
+        let mut instance_writes = Some(Vec::new());
+
+        for i in 0..self.chunks.len() {
+            if !self.chunks[i].dirty {
+                continue;
+            }
+
+            let chunk = UVec2::new(i as u32 % chunk_columns, i as u32 / chunk_columns);
+            let instances = self.build_chunk(chunk, built_with);
+            let chunk = &mut self.chunks[i];
+            chunk.dirty = false;
+
+            // Chunks whose instance count changed no longer fit in their place in the buffer
+            match (chunk.instance_offset, &mut instance_writes) {
+                _ if instances.is_empty() && chunk.instances.is_empty() => (),
+                (Some(offset), Some(instance_writes))
+                    if instances.len() == chunk.instances.len() =>
+                {
+                    instance_writes.push((offset, instances.clone()));
+                }
+                _ => instance_writes = None,
+            }
+            chunk.instances = instances;
+        }
+
+        instance_writes
+    }
+
+    /// Stores where each chunk's instances are in the instance buffer. `instance_offset` maps an
+    /// index into `instances()` to its position in the instance buffer.
+    pub(crate) fn set_instance_offsets(&mut self, instance_offset: impl Fn(usize) -> u32) {
+        let mut first = 0;
+        for chunk in &mut self.chunks {
+            chunk.instance_offset = (!chunk.instances.is_empty()).then(|| instance_offset(first));
+            first += chunk.instances.len();
+        }
+    }
+
+    /// Gets the cached instances of every chunk.
+    pub(crate) fn instances(&self) -> impl Iterator<Item = &Instance> {
+        self.chunks.iter().flat_map(|chunk| &chunk.instances)
+    }
+
+    fn build_chunk(
+        &self,
+        chunk: UVec2,
+        (matrix, texture, sampler, normal_texture): (Mat4, u32, u32, u32),
+    ) -> Vec<Instance> {
+        let min = chunk * TILEMAP_CHUNK_SIZE;
+        let max = (min + UVec2::splat(TILEMAP_CHUNK_SIZE)).min(self.size);
+        let uv_size = Vec2::ONE / self.tileset_size.as_vec2();
+
+        let mut instances = Vec::new();
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let Some(tile) = self.tiles[y as usize * self.size.x as usize + x as usize] else {
+                    continue;
+                };
+
+                let tileset_position = UVec2::new(
+                    tile % self.tileset_size.x,
+                    tile / self.tileset_size.x % self.tileset_size.y,
+                );
+                let uv = tileset_position.as_vec2() * uv_size;
+                let quad = Mat4::from_scale_rotation_translation(
+                    self.tile_size.extend(1.0),
+                    Quat::IDENTITY,
+                    ((Vec2::new(x as f32, y as f32) + 0.5) * self.tile_size).extend(0.0),
+                );
+
+                instances.push(
+                    Instance::new(matrix * quad, texture, sampler)
+                        .with_uv_rect([uv.x, uv.y, uv_size.x, uv_size.y])
+                        .with_normal_texture(normal_texture),
+                );
+            }
+        }
+
+        instances
+    }
+}
+
+/// The cached instances of a tilemap chunk.
+#[derive(Clone)]
+struct TilemapChunk {
+    dirty: bool,
+    instances: Vec<Instance>,
+    /// The position of the chunk's first instance in the instance buffer.
+    instance_offset: Option<u32>,
+}
+
+impl Default for TilemapChunk {
+    fn default() -> Self {
+        Self {
+            dirty: true,
+            instances: Vec::new(),
+            instance_offset: None,
+        }
+    }
+}
@@ -1,31 +1,56 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
 
 use bevy_ecs::{entity::EntityHashSet, prelude::*, system::SystemState};
 use bevy_transform::components::Transform;
 use derive_more::{Deref, DerefMut};
 use render::{
-    glam::Mat4,
-    prelude::{Instance, RenderPipeline, Uniforms, UpdateRenderState},
+    glam::{Mat4, Vec2},
+    prelude::{
+        CameraView, Instance, InstanceBatch, LINEAR_NORMAL_MAP, NO_NORMAL_MAP, PointLight,
+        RecordFrame, RenderPipeline, Uniforms, UpdateRenderState,
+    },
     wgpu::{self, SamplerDescriptor},
 };
 
 use crate::{
-    camera::Camera,
-    prelude::{DenseStorageIndex, Material, Sampler, Texture, Textures},
+    camera::{Camera, ScreenSpace, UiCamera},
+    lighting,
+    nine_slice::NineSlice,
+    prelude::{
+        DenseStorageIndex, Diagnostics, Fonts, FrameRecorder, GizmoBuffer, Material,
+        ParticleEmitter, PostProcesses, Sampler, Screenshot, Shader, Shaders, Text, Texture,
+        TextureDataError, TextureError, Textures, Tilemap,
+    },
     visibility::Visibility,
 };
 
 pub(crate) fn init(world: &mut World) {
     world.init_resource::<RenderInstances>();
     world.init_resource::<RenderTextures>();
+    world.init_resource::<RenderShaders>();
+    world.init_resource::<GizmoBuffer>();
+    world.init_resource::<RenderCameras>();
 
     let transforms = SystemState::new(world);
     let materials = SystemState::new(world);
     let visibility = SystemState::new(world);
+    let nine_slices = SystemState::new(world);
+    let texts = SystemState::new(world);
+    let tilemaps = SystemState::new(world);
+    let particle_emitters = SystemState::new(world);
+    let screen_spaces = SystemState::new(world);
     world.insert_resource(RemovedInstanceComponents {
         transforms,
         materials,
         visibility,
+        nine_slices,
+        texts,
+        tilemaps,
+        particle_emitters,
+        screen_spaces,
     });
 }
 
@@ -33,27 +58,25 @@ pub(crate) fn update_render_state(
     render_pipeline: &mut RenderPipeline,
     world: &mut World,
 ) -> UpdateRenderState {
-    let mut clear_color = None;
-    let uniforms = world
-        .query::<(&Camera, &Transform)>()
-        .single(world)
-        .ok()
-        .map(|(camera, transform)| {
-            clear_color = Some(camera.clear_color);
-            let window_size = render_pipeline.get_window_size();
-            let aspect_ratio = window_size.width as f32 / window_size.height as f32;
-            Uniforms::new(
-                transform.compute_matrix(),
-                Mat4::orthographic_rh(
-                    aspect_ratio * -camera.vertical_scale * 0.5,
-                    aspect_ratio * camera.vertical_scale * 0.5,
-                    -camera.vertical_scale * 0.5,
-                    camera.vertical_scale * 0.5,
-                    camera.near_clip,
-                    camera.far_clip,
-                ),
-            )
-        });
+    // Textures that cameras draw to are created differently so they're rebuilt when that changes
+    let mut camera_targets: Vec<_> = world
+        .query::<&Camera>()
+        .iter(world)
+        .filter_map(|camera| camera.target)
+        .collect();
+    camera_targets.sort_by_key(|target| (target.0, target.1));
+    camera_targets.dedup();
+    if world.resource::<RenderCameras>().targets != camera_targets {
+        world.resource_mut::<Textures>().changed = true;
+    }
+
+    // Textures whose data was replaced keep their GPU textures if their size didn't change
+    let modified_textures = std::mem::take(&mut world.resource_mut::<Textures>().modified);
+    if !world.resource::<Textures>().changed
+        && !write_modified_textures(render_pipeline, world, &modified_textures, &camera_targets)
+    {
+        world.resource_mut::<Textures>().changed = true;
+    }
 
     let mut textures = None;
     let mut texture_resource = world.resource_mut::<Textures>();
@@ -62,37 +85,75 @@ pub(crate) fn update_render_state(
 
         let mut new_textures = Vec::new();
         let mut texture_map = HashMap::new();
+        let mut gpu_textures = HashMap::new();
+        let mut linear_textures = HashSet::new();
+
+        let features = render_pipeline.get_features();
+        let max_dimension = render_pipeline.get_max_texture_dimension();
+        let fallback = Texture::fallback();
+        let mut texture_errors = Vec::new();
 
         for (i, texture) in &texture_resource.textures {
             texture_map.insert(i, new_textures.len() as u32);
 
+            let is_camera_target = camera_targets.contains(&i);
+            // Textures that cameras draw to only need a supported size since their data is ignored
+            // (Compressed textures the GPU can't sample are uploaded from a decompressed copy)
+            let validation = if is_camera_target {
+                texture.validate_size_limit(max_dimension).map(|()| None)
+            } else {
+                texture
+                    .validate()
+                    .and_then(|()| texture.validate_size_limit(max_dimension))
+                    .and_then(|()| decompress_unsupported(texture, features))
+            };
+            // Invalid textures draw the fallback so the other indices stay valid (Textures that
+            // cameras draw to are clamped to the size limit instead)
+            let decompressed;
+            let texture = match validation {
+                Ok(None) => texture,
+                Ok(Some(texture)) => {
+                    decompressed = texture;
+                    &decompressed
+                }
+                Err(error) => {
+                    texture_errors.push(TextureError { texture: i, error });
+                    if is_camera_target { texture } else { &fallback }
+                }
+            };
+            if !is_camera_target && texture.format.is_linear() {
+                linear_textures.insert(i);
+            }
+
             // In the future store the texture views to avoid re-uploading data to the gpu
-            let extent = wgpu::Extent3d {
-                width: texture.size.0,
-                height: texture.size.1,
-                depth_or_array_layers: 1,
+            let (format, mip_level_count) = if is_camera_target {
+                (render_pipeline.get_target_format(), 1)
+            } else {
+                (texture.format.to_wgpu(), texture.mip_level_count)
             };
             let new_texture = render_pipeline.create_texture(&wgpu::TextureDescriptor {
                 label: None,
-                size: extent,
-                mip_level_count: 1,
+                size: wgpu::Extent3d {
+                    width: texture.size.0.clamp(1, max_dimension),
+                    height: texture.size.1.clamp(1, max_dimension),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count,
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                format,
+                usage: if is_camera_target {
+                    wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT
+                } else {
+                    wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST
+                },
                 view_formats: &[],
             });
-            render_pipeline.write_texture(
-                new_texture.as_image_copy(),
-                &texture.data,
-                wgpu::TexelCopyBufferLayout {
-                    offset: 0,
-                    bytes_per_row: Some(extent.width * 4),
-                    rows_per_image: None,
-                },
-                extent,
-            );
+            if !is_camera_target {
+                write_texture_data(render_pipeline, &new_texture, texture);
+            }
             new_textures.push(new_texture.create_view(&wgpu::TextureViewDescriptor::default()));
+            gpu_textures.insert(i, new_texture);
         }
 
         let mut new_samplers = Vec::new();
@@ -110,55 +171,629 @@ pub(crate) fn update_render_state(
         world.insert_resource(RenderTextures {
             textures: texture_map,
             samplers: sampler_map,
+            gpu_textures,
+            linear_textures,
         });
+        world.send_event_batch(texture_errors);
+    }
+
+    let mut shaders = None;
+    let mut shader_resource = world.resource_mut::<Shaders>();
+    if shader_resource.changed {
+        shader_resource.changed = false;
+
+        let mut new_shaders = Vec::new();
+        let mut shader_map = HashMap::new();
+
+        for (i, shader) in &shader_resource.shaders {
+            shader_map.insert(i, new_shaders.len() as u32);
+            new_shaders.push(shader.clone());
+        }
+
+        shaders = Some(new_shaders);
+
+        world.insert_resource(RenderShaders(shader_map));
     }
 
+    let (cameras, render_cameras, lights) = extract_cameras(render_pipeline, world, camera_targets);
+    let cameras_changed = {
+        let old_render_cameras = world.resource::<RenderCameras>();
+        old_render_cameras.world != render_cameras.world
+            || old_render_cameras.ui != render_cameras.ui
+    };
+    world.insert_resource(render_cameras);
+
     let instances_changed = world
         .query_filtered::<(), (
             (With<Transform>, With<Material>, With<Visibility>),
-            Or<(Changed<Transform>, Changed<Material>, Changed<Visibility>)>,
+            Or<(
+                Changed<Transform>,
+                Changed<Material>,
+                Changed<Visibility>,
+                Changed<NineSlice>,
+                // Emitters change every frame, their particles are rebuilt every frame instead
+                Added<ParticleEmitter>,
+                Changed<ScreenSpace>,
+            )>,
         )>()
         .iter(world)
         .next()
-        .is_some();
+        .is_some()
+        || world
+            .query_filtered::<(), (
+                (With<Transform>, With<Text>, With<Visibility>),
+                Or<(
+                    Changed<Transform>,
+                    Changed<Text>,
+                    Changed<Visibility>,
+                    Changed<ScreenSpace>,
+                )>,
+            )>()
+            .iter(world)
+            .next()
+            .is_some();
+    let mut font_resource = world.resource_mut::<Fonts>();
+    let fonts_changed = std::mem::take(&mut font_resource.changed);
     let instances_removed = world.resource_scope(
         |world: &mut World, mut removed_instance_components: Mut<RemovedInstanceComponents>| {
             removed_instance_components.any_render_components_removed(world)
         },
     );
 
+    let mut instances_changed = instances_changed
+        || instances_removed
+        || fonts_changed
+        || cameras_changed
+        || textures.is_some()
+        || shaders.is_some();
+    // Tilemaps whose tiles changed only rewrite their rebuilt chunks if nothing else changed
+    let mut instance_writes = Vec::new();
+    if !instances_changed && !update_tilemap_chunks(world, &mut instance_writes) {
+        instance_writes.clear();
+        instances_changed = true;
+    }
+
     let mut instances = None;
-    if instances_changed || instances_removed || textures.is_some() {
+    if instances_changed {
         world.try_resource_scope(|world, render_textures: Mut<RenderTextures>| {
-            let (render_instances, gpu_instances) = world
-                .query::<(Entity, &Transform, &Material, &Visibility)>()
-                .iter(world)
-                .filter(|&(_, _, _, visibility)| *visibility == Visibility::Visible)
-                .filter_map(|(entity, transform, material, _)| {
-                    match (
-                        render_textures.textures.get(&material.texture),
-                        render_textures.samplers.get(&material.sampler),
-                    ) {
-                        (Some(&texture), Some(&sampler)) => Some((
-                            entity,
-                            Instance::new(transform.compute_matrix(), texture, sampler),
-                        )),
-                        _ => None,
+            let render_cameras = world.resource::<RenderCameras>().clone();
+            let mut render_instances = EntityHashSet::default();
+            let mut shader_instances = Vec::new();
+            // The tilemaps and where their instances start in `shader_instances`
+            let mut tilemap_instances = Vec::new();
+
+            // Tilemaps update their cached chunk instances so they need mutable access
+            world.resource_scope(|world, render_shaders: Mut<RenderShaders>| {
+                let mut query = world.query::<(
+                    Entity,
+                    &Transform,
+                    &Material,
+                    &Visibility,
+                    Has<ScreenSpace>,
+                    &mut Tilemap,
+                )>();
+                for (entity, transform, material, visibility, screen_space, mut tilemap) in
+                    query.iter_mut(world)
+                {
+                    if *visibility != Visibility::Visible {
+                        continue;
                     }
-                })
-                .unzip();
+
+                    let shader = render_shaders.get_material_shader(material);
+                    let (Some(layer), Some(&texture), Some(&sampler)) = (
+                        render_cameras.layer(screen_space),
+                        render_textures.textures.get(&material.texture.index()),
+                        render_textures.samplers.get(&material.sampler.index()),
+                    ) else {
+                        continue;
+                    };
+
+                    render_instances.insert(entity);
+                    tilemap_instances.push((entity, shader_instances.len()));
+
+                    let tilemap = tilemap.bypass_change_detection();
+                    tilemap.update_chunks(
+                        transform.compute_matrix(),
+                        texture,
+                        sampler,
+                        render_textures.get_normal_texture(material),
+                    );
+                    shader_instances.extend(
+                        tilemap
+                            .instances()
+                            .map(|&instance| ((layer, shader), instance)),
+                    );
+                }
+            });
+
+            let mut query = world.query_filtered::<(
+                Entity,
+                &Transform,
+                &Material,
+                &Visibility,
+                Has<ScreenSpace>,
+                Option<&NineSlice>,
+            ), (Without<Tilemap>, Without<ParticleEmitter>)>();
+            let render_shaders = world.resource::<RenderShaders>();
+            let texture_resource = world.resource::<Textures>();
+
+            for (entity, transform, material, visibility, screen_space, nine_slice) in
+                query.iter(world)
+            {
+                if *visibility != Visibility::Visible {
+                    continue;
+                }
+
+                let shader = render_shaders.get_material_shader(material);
+                let Some(layer) = render_cameras.layer(screen_space) else {
+                    continue;
+                };
+                let (Some(&texture), Some(&sampler)) = (
+                    render_textures.textures.get(&material.texture.index()),
+                    render_textures.samplers.get(&material.sampler.index()),
+                ) else {
+                    continue;
+                };
+
+                render_instances.insert(entity);
+
+                let normal_texture = render_textures.get_normal_texture(material);
+                match (
+                    nine_slice,
+                    texture_resource.textures.get(material.texture.index()),
+                ) {
+                    (Some(nine_slice), Some(texture_data)) => {
+                        shader_instances.extend(
+                            nine_slice
+                                .quads(transform, texture_data.size)
+                                .into_iter()
+                                .map(|(matrix, uv_rect)| {
+                                    (
+                                        (layer, shader),
+                                        Instance::new(matrix, texture, sampler)
+                                            .with_uv_rect(uv_rect)
+                                            .with_normal_texture(normal_texture),
+                                    )
+                                }),
+                        );
+                    }
+                    _ => shader_instances.push((
+                        (layer, shader),
+                        Instance::new(transform.compute_matrix(), texture, sampler)
+                            .with_uv_rect(material.uv_rect)
+                            .with_normal_texture(normal_texture),
+                    )),
+                }
+            }
+
+            let mut query =
+                world.query::<(Entity, &Transform, &Text, &Visibility, Has<ScreenSpace>)>();
+            let font_resource = world.resource::<Fonts>();
+            for (entity, transform, text, visibility, screen_space) in query.iter(world) {
+                if *visibility != Visibility::Visible {
+                    continue;
+                }
+
+                let (Some(layer), Some(font), Some(&sampler)) = (
+                    render_cameras.layer(screen_space),
+                    font_resource.fonts.get(text.font),
+                    render_textures.samplers.get(&text.sampler),
+                ) else {
+                    continue;
+                };
+
+                render_instances.insert(entity);
+
+                let color = [
+                    text.color.r as f32,
+                    text.color.g as f32,
+                    text.color.b as f32,
+                    text.color.a as f32,
+                ];
+                shader_instances.extend(text.glyph_quads(transform, font).into_iter().filter_map(
+                    |(matrix, uv_rect, page_texture)| {
+                        let &texture = render_textures.textures.get(&page_texture)?;
+                        Some((
+                            (layer, None),
+                            Instance::new(matrix, texture, sampler)
+                                .with_uv_rect(uv_rect)
+                                .with_color(color),
+                        ))
+                    },
+                ));
+            }
+
+            // Group the instances by layer and shader so each shader is drawn with one draw call
+            // per camera (The sorted order is kept to find where the tilemap chunks end up)
+            let mut order: Vec<usize> = (0..shader_instances.len()).collect();
+            order.sort_by_key(|&i| shader_instances[i].0);
+
+            let instance_batches = batch_instances(
+                order.iter().map(|&i| shader_instances[i].0),
+                &render_cameras,
+            );
+
+            let gpu_instances = order.iter().map(|&i| shader_instances[i].1).collect();
+
+            // Tilemap chunks stay contiguous since the sort is stable, so they can be rewritten in
+            // place when their tiles change
+            let mut sorted_positions = vec![0; order.len()];
+            for (position, &i) in order.iter().enumerate() {
+                sorted_positions[i] = position as u32;
+            }
+            for (entity, first) in tilemap_instances {
+                if let Some(mut tilemap) = world.get_mut::<Tilemap>(entity) {
+                    tilemap
+                        .bypass_change_detection()
+                        .set_instance_offsets(|i| sorted_positions[first + i]);
+                }
+            }
 
             world.insert_resource(RenderInstances(render_instances));
-            instances = Some(gpu_instances);
+            instances = Some((gpu_instances, instance_batches));
+        });
+    }
+
+    let dynamic_instances = Some(extract_particles(world));
+
+    // Hidden entities are counted every frame since the instances aren't rebuilt every frame
+    let culled_count = world
+        .query_filtered::<&Visibility, (With<Transform>, Or<(With<Material>, With<Text>)>)>()
+        .iter(world)
+        .filter(|&&visibility| visibility != Visibility::Visible)
+        .count();
+    world.resource_mut::<Diagnostics>().culled_count = culled_count as u32;
+
+    let mut post_processes = None;
+    let mut post_process_resource = world.resource_mut::<PostProcesses>();
+    if post_process_resource.changed {
+        post_process_resource.changed = false;
+        post_processes = Some(post_process_resource.post_processes.clone());
+    }
+
+    let lines = std::mem::take(&mut world.resource_mut::<GizmoBuffer>().lines);
+    let screenshots = world
+        .resource_mut::<Events<Screenshot>>()
+        .drain()
+        .map(|screenshot| screenshot.path)
+        .collect();
+
+    let mut frame_recorder = world.resource_mut::<FrameRecorder>();
+    let record_frame = frame_recorder
+        .recording
+        .as_ref()
+        .map(|recording| RecordFrame {
+            output: recording.output.clone(),
+            frame: frame_recorder.frame,
         });
+    if let Some(recording) = &frame_recorder.recording {
+        let frame = frame_recorder.frame + 1;
+        if recording
+            .frame_count
+            .is_some_and(|frame_count| frame >= frame_count)
+        {
+            frame_recorder.recording = None;
+        }
+        frame_recorder.frame = frame;
     }
 
     UpdateRenderState {
-        clear_color: clear_color.unwrap_or(wgpu::Color::BLACK),
-        uniforms,
+        cameras: Some(cameras),
         instances,
+        instance_writes,
+        dynamic_instances,
         textures,
+        shaders,
+        lights: Some(lights),
+        lines: Some(lines),
+        post_processes,
+        screenshots,
+        record_frame,
+    }
+}
+
+/// Builds the instances of every particle. (They move every frame so they're kept apart from the
+/// other instances)
+fn extract_particles(world: &mut World) -> (Vec<Instance>, Vec<InstanceBatch>) {
+    let mut query = world.query::<(
+        &Transform,
+        &Material,
+        &Visibility,
+        Has<ScreenSpace>,
+        &ParticleEmitter,
+    )>();
+    let render_textures = world.resource::<RenderTextures>();
+    let render_shaders = world.resource::<RenderShaders>();
+    let render_cameras = world.resource::<RenderCameras>();
+
+    let mut shader_instances = Vec::new();
+    for (transform, material, visibility, screen_space, emitter) in query.iter(world) {
+        if *visibility != Visibility::Visible {
+            continue;
+        }
+
+        let shader = render_shaders.get_material_shader(material);
+        let (Some(layer), Some(&texture), Some(&sampler)) = (
+            render_cameras.layer(screen_space),
+            render_textures.textures.get(&material.texture.index()),
+            render_textures.samplers.get(&material.sampler.index()),
+        ) else {
+            continue;
+        };
+
+        let normal_texture = render_textures.get_normal_texture(material);
+        shader_instances.extend(emitter.particle_quads(transform.translation.z).map(
+            |(matrix, color)| {
+                (
+                    (layer, shader),
+                    Instance::new(matrix, texture, sampler)
+                        .with_uv_rect(material.uv_rect)
+                        .with_color(color)
+                        .with_normal_texture(normal_texture),
+                )
+            },
+        ));
+    }
+
+    shader_instances.sort_by_key(|&(key, _)| key);
+    let instance_batches =
+        batch_instances(shader_instances.iter().map(|&(key, _)| key), render_cameras);
+    let instances = shader_instances
+        .into_iter()
+        .map(|(_, instance)| instance)
+        .collect();
+
+    (instances, instance_batches)
+}
+
+/// Splits instances sorted by layer and shader into a batch for each camera of their layer.
+fn batch_instances(
+    keys: impl Iterator<Item = (bool, Option<u32>)>,
+    render_cameras: &RenderCameras,
+) -> Vec<InstanceBatch> {
+    let mut layer_batches: Vec<(bool, Option<u32>, Range<u32>)> = Vec::new();
+    for (i, (layer, shader)) in keys.enumerate() {
+        match layer_batches.last_mut() {
+            Some((batch_layer, batch_shader, instances))
+                if *batch_layer == layer && *batch_shader == shader =>
+            {
+                instances.end = i as u32 + 1
+            }
+            _ => layer_batches.push((layer, shader, i as u32..i as u32 + 1)),
+        }
+    }
+
+    // Every camera of a layer draws the same instances
+    layer_batches
+        .into_iter()
+        .flat_map(|(layer, shader, instances)| {
+            render_cameras
+                .get_cameras(layer)
+                .iter()
+                .map(move |&camera| InstanceBatch {
+                    camera,
+                    shader,
+                    instances: instances.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Rebuilds the dirty chunks of the tilemaps whose tiles changed and adds them to
+/// `instance_writes`. Returns `false` if a chunk's instance count changed and all instances need to
+/// be rebuilt.
+fn update_tilemap_chunks(
+    world: &mut World,
+    instance_writes: &mut Vec<(u32, Vec<Instance>)>,
+) -> bool {
+    world.resource_scope(|world, render_instances: Mut<RenderInstances>| {
+        let mut query = world.query_filtered::<(Entity, &mut Tilemap), Changed<Tilemap>>();
+        let mut fits = true;
+        for (entity, mut tilemap) in query.iter_mut(world) {
+            // Tilemaps that aren't drawn are rebuilt when they are
+            if !render_instances.contains(&entity) {
+                continue;
+            }
+
+            match tilemap.bypass_change_detection().update_dirty_chunks() {
+                Some(writes) => instance_writes.extend(writes),
+                None => fits = false,
+            }
+        }
+
+        fits
+    })
+}
+
+/// Re-uploads the modified textures to their GPU textures. Returns `false` if a texture can't be
+/// written in place and all textures need to be rebuilt.
+fn write_modified_textures(
+    render_pipeline: &RenderPipeline,
+    world: &World,
+    modified_textures: &[DenseStorageIndex<Texture>],
+    camera_targets: &[DenseStorageIndex<Texture>],
+) -> bool {
+    let texture_resource = world.resource::<Textures>();
+    let render_textures = world.resource::<RenderTextures>();
+    let features = render_pipeline.get_features();
+
+    for index in modified_textures {
+        // The data of textures that cameras draw to is ignored
+        if camera_targets.contains(index) {
+            continue;
+        }
+        let Some(texture) = texture_resource.textures.get(*index) else {
+            continue;
+        };
+        let Some(gpu_texture) = render_textures.gpu_textures.get(index) else {
+            return false;
+        };
+        let Ok(decompressed) = texture
+            .validate()
+            .and_then(|()| decompress_unsupported(texture, features))
+        else {
+            return false;
+        };
+        let texture = decompressed.as_ref().unwrap_or(texture);
+
+        let extent = wgpu::Extent3d {
+            width: texture.size.0,
+            height: texture.size.1,
+            depth_or_array_layers: 1,
+        };
+        if gpu_texture.size() != extent
+            || gpu_texture.format() != texture.format.to_wgpu()
+            || gpu_texture.mip_level_count() != texture.mip_level_count
+        {
+            return false;
+        }
+
+        write_texture_data(render_pipeline, gpu_texture, texture);
+    }
+
+    true
+}
+
+/// Decompresses a valid compressed texture the GPU can't sample into a copy to upload instead.
+/// Returns `None` if the GPU samples the texture directly.
+fn decompress_unsupported(
+    texture: &Texture,
+    features: wgpu::Features,
+) -> Result<Option<Texture>, TextureDataError> {
+    if features.contains(texture.format.required_features()) {
+        Ok(None)
+    } else {
+        texture.decompress().map(Some)
+    }
+}
+
+/// Uploads each mip level of a valid texture.
+fn write_texture_data(
+    render_pipeline: &RenderPipeline,
+    gpu_texture: &wgpu::Texture,
+    texture: &Texture,
+) {
+    let layouts = texture
+        .mip_level_layouts()
+        .expect("valid textures have mip level layouts");
+
+    for (level, layout) in layouts.iter().enumerate() {
+        render_pipeline.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: gpu_texture,
+                mip_level: level as u32,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &texture.data[layout.offset..layout.offset + layout.len()],
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(layout.bytes_per_row),
+                rows_per_image: None,
+            },
+            // Compressed mip levels are copied in whole blocks
+            wgpu::Extent3d {
+                width: layout.physical_size.0,
+                height: layout.physical_size.1,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
+/// Gets the cameras in draw order: the cameras that draw to textures, then the world camera and
+/// the UI camera.
+fn extract_cameras(
+    render_pipeline: &RenderPipeline,
+    world: &mut World,
+    targets: Vec<DenseStorageIndex<Texture>>,
+) -> (Vec<CameraView>, RenderCameras, Vec<PointLight>) {
+    let window_size = render_pipeline.get_window_size();
+    let mut cameras = Vec::new();
+    let mut render_cameras = RenderCameras {
+        targets,
+        ..Default::default()
+    };
+
+    let mut world_cameras: Vec<_> = world
+        .query::<(Entity, &Camera, &Transform)>()
+        .iter(world)
+        .map(|(entity, camera, transform)| (entity, *camera, *transform))
+        .collect();
+    // Draw to the textures first so the window can show them in the same frame
+    world_cameras.sort_by_key(|&(entity, camera, _)| (camera.target.is_none(), entity));
+
+    // Point lights are picked around the window camera
+    let light_origin = world_cameras
+        .iter()
+        .find(|(_, camera, _)| camera.target.is_none())
+        .or(world_cameras.first())
+        .map_or(Vec2::ZERO, |(_, _, transform)| {
+            transform.translation.truncate()
+        });
+    let lighting = lighting::extract_lights(world, light_origin);
+
+    let render_textures = world.resource::<RenderTextures>();
+    let textures = world.resource::<Textures>();
+    let mut has_window_camera = false;
+    for (_, camera, transform) in world_cameras {
+        let (target, size) = match camera.target {
+            Some(target) => {
+                let (Some(&render_target), Some(texture)) = (
+                    render_textures.textures.get(&target),
+                    textures.textures.get(target),
+                ) else {
+                    continue;
+                };
+                (Some(render_target), texture.size)
+            }
+            // Only one camera can draw to the window
+            None if has_window_camera => continue,
+            None => {
+                has_window_camera = true;
+                (None, (window_size.width, window_size.height))
+            }
+        };
+
+        let aspect_ratio = size.0 as f32 / size.1 as f32;
+        let mut uniforms = Uniforms::new(
+            transform.compute_matrix(),
+            Mat4::orthographic_rh(
+                aspect_ratio * -camera.vertical_scale * 0.5,
+                aspect_ratio * camera.vertical_scale * 0.5,
+                -camera.vertical_scale * 0.5,
+                camera.vertical_scale * 0.5,
+                camera.near_clip,
+                camera.far_clip,
+            ),
+        );
+        if let Some((ambient_light, point_lights)) = &lighting {
+            uniforms = uniforms.with_lighting(*ambient_light, point_lights.len() as u32);
+        }
+
+        render_cameras.world.push(cameras.len() as u32);
+        cameras.push(CameraView {
+            uniforms,
+            clear_color: Some(camera.clear_color),
+            target,
+        });
     }
+
+    if let Ok(ui_camera) = world.query::<&UiCamera>().single(world) {
+        render_cameras.ui.push(cameras.len() as u32);
+        cameras.push(CameraView {
+            uniforms: Uniforms::new(
+                Mat4::IDENTITY,
+                ui_camera.projection(window_size.width as f32, window_size.height as f32),
+            ),
+            // Draw over the world camera
+            clear_color: (!has_window_camera).then_some(wgpu::Color::BLACK),
+            target: None,
+        });
+    }
+
+    let lights = lighting.map_or_else(Vec::new, |(_, point_lights)| point_lights);
+    (cameras, render_cameras, lights)
 }
 
 #[derive(Default, Deref, DerefMut, Resource)]
@@ -168,6 +803,64 @@ struct RenderInstances(pub EntityHashSet);
 struct RenderTextures {
     textures: HashMap<DenseStorageIndex<Texture>, u32>,
     samplers: HashMap<DenseStorageIndex<Sampler>, u32>,
+    /// The GPU textures so modified textures can be re-uploaded on their own.
+    gpu_textures: HashMap<DenseStorageIndex<Texture>, wgpu::Texture>,
+    /// The textures whose data isn't sRGB encoded.
+    linear_textures: HashSet<DenseStorageIndex<Texture>>,
+}
+
+/// The render camera indices of the world and UI cameras.
+#[derive(Default, Clone, Resource)]
+struct RenderCameras {
+    /// The cameras that draw world-space entities, including the ones that draw to textures.
+    world: Vec<u32>,
+    ui: Vec<u32>,
+    /// The textures that cameras draw to.
+    targets: Vec<DenseStorageIndex<Texture>>,
+}
+
+impl RenderCameras {
+    /// Gets the render camera indices of a layer. (`true` is screen space)
+    fn get_cameras(&self, layer: bool) -> &[u32] {
+        if layer { &self.ui } else { &self.world }
+    }
+
+    /// Gets the layer of an entity or `None` if no camera draws it.
+    fn layer(&self, screen_space: bool) -> Option<bool> {
+        (!self.get_cameras(screen_space).is_empty()).then_some(screen_space)
+    }
+}
+
+impl RenderTextures {
+    /// Gets the render texture index of a material's normal map or `NO_NORMAL_MAP` if it has none.
+    /// (Flagged with `LINEAR_NORMAL_MAP` if the texture is linear)
+    fn get_normal_texture(&self, material: &Material) -> u32 {
+        let Some(normal_map) = &material.normal_map else {
+            return NO_NORMAL_MAP;
+        };
+        let Some(&texture) = self.textures.get(&normal_map.index()) else {
+            return NO_NORMAL_MAP;
+        };
+
+        if self.linear_textures.contains(&normal_map.index()) {
+            texture | LINEAR_NORMAL_MAP
+        } else {
+            texture
+        }
+    }
+}
+
+#[derive(Default, Deref, DerefMut, Resource)]
+struct RenderShaders(HashMap<DenseStorageIndex<Shader>, u32>);
+
+impl RenderShaders {
+    /// Gets the render shader index of a material or `None` for the main shader. (Materials whose
+    /// shader doesn't exist fall back to the main shader)
+    fn get_material_shader(&self, material: &Material) -> Option<u32> {
+        material
+            .shader
+            .and_then(|shader| self.get(&shader).copied())
+    }
 }
 
 #[derive(Resource)]
@@ -175,6 +868,11 @@ struct RemovedInstanceComponents {
     transforms: SystemState<RemovedComponents<'static, 'static, Transform>>,
     materials: SystemState<RemovedComponents<'static, 'static, Material>>,
     visibility: SystemState<RemovedComponents<'static, 'static, Visibility>>,
+    nine_slices: SystemState<RemovedComponents<'static, 'static, NineSlice>>,
+    texts: SystemState<RemovedComponents<'static, 'static, Text>>,
+    tilemaps: SystemState<RemovedComponents<'static, 'static, Tilemap>>,
+    particle_emitters: SystemState<RemovedComponents<'static, 'static, ParticleEmitter>>,
+    screen_spaces: SystemState<RemovedComponents<'static, 'static, ScreenSpace>>,
 }
 
 impl RemovedInstanceComponents {
@@ -185,23 +883,40 @@ impl RemovedInstanceComponents {
                 self.transforms
                     .get(world)
                     .read()
-                    .filter(|entity| render_instances.contains(entity))
-                    .next()
-                    .is_some()
+                    .any(|entity| render_instances.contains(&entity))
                     || self
                         .materials
                         .get(world)
                         .read()
-                        .filter(|entity| render_instances.contains(entity))
-                        .next()
-                        .is_some()
+                        .any(|entity| render_instances.contains(&entity))
                     || self
                         .visibility
                         .get(world)
                         .read()
-                        .filter(|entity| render_instances.contains(entity))
-                        .next()
-                        .is_some()
+                        .any(|entity| render_instances.contains(&entity))
+                    || self
+                        .nine_slices
+                        .get(world)
+                        .read()
+                        .any(|entity| render_instances.contains(&entity))
+                    || self
+                        .texts
+                        .get(world)
+                        .read()
+                        .any(|entity| render_instances.contains(&entity))
+                    || self
+                        .tilemaps
+                        .get(world)
+                        .read()
+                        .any(|entity| render_instances.contains(&entity))
+                    // Emitters aren't in `RenderInstances` since their particles are rebuilt every
+                    // frame, but their entities may need to be drawn as sprites again
+                    || self.particle_emitters.get(world).read().count() > 0
+                    || self
+                        .screen_spaces
+                        .get(world)
+                        .read()
+                        .any(|entity| render_instances.contains(&entity))
             },
         )
     }
@@ -1,7 +1,8 @@
 use bevy_ecs::prelude::*;
 
 /// Defines if an entity is visible or not.
-#[derive(Default, Clone, Copy, PartialEq, Eq, Component)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Component)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Visibility {
     /// The entity is visible.
     #[default]
@@ -33,7 +33,7 @@ where
             capacity: data.len(),
             length: data.len(),
             label: label.map(String::from),
-            _marker: PhantomData::default(),
+            _marker: PhantomData,
         }
     }
 
@@ -61,6 +61,18 @@ where
 
         resize
     }
+
+    /// Overwrites part of the buffer starting at the element `offset`. Writes past the buffer
+    /// length are ignored.
+    pub(crate) fn write_range(&self, queue: &wgpu::Queue, offset: usize, data: &[T]) {
+        if offset + data.len() <= self.length {
+            queue.write_buffer(
+                &self.buffer,
+                (offset * size_of::<T>()) as wgpu::BufferAddress,
+                bytemuck::cast_slice(data),
+            );
+        }
+    }
 }
 
 impl<T> ArrayBuffer<T> {
@@ -0,0 +1,223 @@
+use std::{
+    mem,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::Duration,
+};
+
+use wgpu::BufferAsyncError;
+
+/// The maximum number of passes timed per frame. (Later passes aren't timed)
+const MAX_TIMED_PASSES: u32 = 64;
+/// The maximum number of frames waiting to be read back before frames are skipped.
+const MAX_PENDING_FRAMES: usize = 4;
+
+/// The time the GPU spent on a render pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassTiming {
+    pub label: String,
+    pub duration: Duration,
+}
+
+/// A frame's timestamps copied to a buffer that's waiting to be read back.
+struct PendingTimings {
+    buffer: wgpu::Buffer,
+    labels: Vec<String>,
+    mapped: Option<Receiver<Result<(), BufferAsyncError>>>,
+}
+
+/// Times render passes with timestamp queries. (Does nothing if `TIMESTAMP_QUERY` isn't
+/// supported)
+pub(crate) struct GpuProfiler {
+    /// `None` if timestamp queries aren't supported.
+    query_set: Option<wgpu::QuerySet>,
+    resolve_buffer: Option<wgpu::Buffer>,
+    /// The nanoseconds per timestamp tick.
+    timestamp_period: f32,
+    /// The labels of the passes timed this frame, two queries each.
+    labels: Vec<String>,
+    /// Whether passes are timed this frame.
+    active: bool,
+    pending: Vec<PendingTimings>,
+    /// Read back buffers that can be reused.
+    free_buffers: Vec<wgpu::Buffer>,
+    /// The latest timings that weren't taken yet.
+    timings: Option<Vec<PassTiming>>,
+}
+
+impl GpuProfiler {
+    pub(crate) fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let supported = device.features().contains(wgpu::Features::TIMESTAMP_QUERY);
+        let query_set = supported.then(|| {
+            device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("Timestamp Query Set"),
+                ty: wgpu::QueryType::Timestamp,
+                count: MAX_TIMED_PASSES * 2,
+            })
+        });
+        let resolve_buffer = supported.then(|| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Timestamp Resolve Buffer"),
+                size: Self::buffer_size(),
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            })
+        });
+
+        Self {
+            query_set,
+            resolve_buffer,
+            timestamp_period: queue.get_timestamp_period(),
+            labels: Vec::new(),
+            active: false,
+            pending: Vec::new(),
+            free_buffers: Vec::new(),
+            timings: None,
+        }
+    }
+
+    fn buffer_size() -> u64 {
+        MAX_TIMED_PASSES as u64 * 2 * size_of::<u64>() as u64
+    }
+
+    pub(crate) fn is_supported(&self) -> bool {
+        self.query_set.is_some()
+    }
+
+    /// Starts timing a frame. (Frames are skipped while too many are waiting to be read back)
+    pub(crate) fn begin_frame(&mut self) {
+        self.labels.clear();
+        self.active = self.is_supported() && self.pending.len() < MAX_PENDING_FRAMES;
+    }
+
+    /// Gets the timestamp writes that time a pass or `None` if it isn't timed.
+    pub(crate) fn timestamp_writes(
+        &mut self,
+        label: impl Into<String>,
+    ) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let query_set = self.query_set.as_ref()?;
+        if !self.active || self.labels.len() as u32 >= MAX_TIMED_PASSES {
+            return None;
+        }
+
+        let index = self.labels.len() as u32 * 2;
+        self.labels.push(label.into());
+
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set,
+            beginning_of_pass_write_index: Some(index),
+            end_of_pass_write_index: Some(index + 1),
+        })
+    }
+
+    /// Copies the timestamps of this frame to a buffer that's read back after submitting.
+    pub(crate) fn resolve(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        let (Some(query_set), Some(resolve_buffer)) = (&self.query_set, &self.resolve_buffer)
+        else {
+            return;
+        };
+        if self.labels.is_empty() {
+            return;
+        }
+
+        let query_count = self.labels.len() as u32 * 2;
+        let buffer = self.free_buffers.pop().unwrap_or_else(|| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Timestamp Read Back Buffer"),
+                size: Self::buffer_size(),
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            })
+        });
+
+        encoder.resolve_query_set(query_set, 0..query_count, resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            resolve_buffer,
+            0,
+            &buffer,
+            0,
+            query_count as u64 * size_of::<u64>() as u64,
+        );
+
+        self.pending.push(PendingTimings {
+            buffer,
+            labels: mem::take(&mut self.labels),
+            mapped: None,
+        });
+    }
+
+    /// Starts mapping the buffers of submitted frames.
+    pub(crate) fn map_submitted(&mut self) {
+        for timings in &mut self.pending {
+            if timings.mapped.is_none() {
+                let (sender, receiver) = mpsc::channel();
+                timings
+                    .buffer
+                    .slice(..)
+                    .map_async(wgpu::MapMode::Read, move |result| {
+                        let _ = sender.send(result);
+                    });
+                timings.mapped = Some(receiver);
+            }
+        }
+    }
+
+    /// Checks the device without blocking and reads back the timings whose buffers were mapped.
+    pub(crate) fn update(&mut self, device: &wgpu::Device) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        if let Err(error) = device.poll(wgpu::PollType::Poll) {
+            eprintln!("failed to poll the device: {error}");
+        }
+
+        while let Some(timings) = self.pending.first() {
+            let Some(mapped) = &timings.mapped else {
+                break;
+            };
+
+            match mapped.try_recv() {
+                Ok(result) => {
+                    let timings = self.pending.remove(0);
+                    match result {
+                        Ok(()) => self.read(timings),
+                        Err(error) => eprintln!("failed to read back GPU timings: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.pending.remove(0);
+                    eprintln!("failed to read back GPU timings: the buffer mapping was dropped");
+                }
+            }
+        }
+    }
+
+    /// Converts the timestamps to pass durations and reuses the buffer.
+    fn read(&mut self, timings: PendingTimings) {
+        let mut passes = Vec::with_capacity(timings.labels.len());
+        {
+            let mapped = timings.buffer.slice(..).get_mapped_range();
+            let timestamps: &[u64] = bytemuck::cast_slice(&mapped);
+
+            for (label, pair) in timings.labels.into_iter().zip(timestamps.chunks_exact(2)) {
+                let ticks = pair[1].saturating_sub(pair[0]);
+                passes.push(PassTiming {
+                    label,
+                    duration: Duration::from_nanos(
+                        (ticks as f64 * self.timestamp_period as f64) as u64,
+                    ),
+                });
+            }
+        }
+        timings.buffer.unmap();
+
+        self.free_buffers.push(timings.buffer);
+        self.timings = Some(passes);
+    }
+
+    /// Takes the latest timings read back since the last call.
+    pub(crate) fn take_timings(&mut self) -> Option<Vec<PassTiming>> {
+        self.timings.take()
+    }
+}
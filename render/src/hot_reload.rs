@@ -0,0 +1,15 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+/// How often watched files are checked for modification when hot-reloading.
+pub const HOT_RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Gets the last modification time of a file or `None` if it can't be read.
+pub fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -7,24 +7,58 @@ use glam::Mat4;
 pub struct Instance {
     /// Transposed affine matrix (last row is 0,0,0,1)
     pub transform: [[f32; 4]; 3],
+    /// The region of the texture to draw as `[u, v, width, height]` in texture coordinates.
+    pub uv_rect: [f32; 4],
+    /// The color multiplied with the texture color.
+    pub color: [f32; 4],
     /// The texture to use when drawing this instance.
     pub texture_index: u32,
     /// The sampler to use when sampling the texture.
     pub sampler_index: u32,
-    _padding: [u32; 2],
+    /// The normal map texture or `NO_NORMAL_MAP` to shade the instance as flat.
+    pub normal_texture_index: u32,
+    _padding: u32,
 }
 
+/// The `Instance::normal_texture_index` of instances without a normal map.
+pub const NO_NORMAL_MAP: u32 = u32::MAX;
+
+/// Set in `Instance::normal_texture_index` if the normal map texture is linear. (Normal maps in
+/// sRGB textures are converted back before decoding)
+pub const LINEAR_NORMAL_MAP: u32 = 1 << 31;
+
 impl Instance {
     /// Creates a new `Instance` with the provided options. (The transformation matrix will be
     /// packed to save space)
     pub fn new(transform: Mat4, texture_index: u32, sampler_index: u32) -> Self {
         Self {
             transform: pack_transform(transform),
+            uv_rect: [0.0, 0.0, 1.0, 1.0],
+            color: [1.0; 4],
             texture_index,
             sampler_index,
+            normal_texture_index: NO_NORMAL_MAP,
             ..Default::default()
         }
     }
+
+    /// Sets the region of the texture to draw as `[u, v, width, height]` in texture coordinates.
+    pub fn with_uv_rect(mut self, uv_rect: [f32; 4]) -> Self {
+        self.uv_rect = uv_rect;
+        self
+    }
+
+    /// Sets the color multiplied with the texture color.
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the normal map texture used for lighting. (Sampled with the instance's sampler)
+    pub fn with_normal_texture(mut self, normal_texture_index: u32) -> Self {
+        self.normal_texture_index = normal_texture_index;
+        self
+    }
 }
 
 fn pack_transform(mut transform: Mat4) -> [[f32; 4]; 3] {
@@ -1,14 +1,25 @@
+pub mod gpu_timings;
+pub mod hot_reload;
 pub mod instance;
+pub mod light;
+pub mod line;
+pub mod post_process;
+pub mod recording;
 pub mod render_app;
 pub mod render_pipeline;
 pub mod render_state;
+pub mod shader;
 pub mod uniforms;
 
 mod array_buffer;
+mod screenshot;
 mod vertex;
 
 pub use {glam, wgpu, winit};
 
 pub mod prelude {
-    pub use crate::{instance::*, render_app::*, render_pipeline::*, render_state::*, uniforms::*};
+    pub use crate::{
+        gpu_timings::*, hot_reload::*, instance::*, light::*, line::*, post_process::*,
+        recording::*, render_app::*, render_pipeline::*, render_state::*, shader::*, uniforms::*,
+    };
 }
@@ -0,0 +1,27 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+
+/// Holds point light data that will be passed to the shader.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable, Default)]
+pub struct PointLight {
+    /// The world position. (z is the height above the lit sprites)
+    pub position: [f32; 3],
+    /// The distance where the light fades out completely.
+    pub radius: f32,
+    /// The linear RGB color premultiplied by the intensity.
+    pub color: [f32; 3],
+    _padding: u32,
+}
+
+impl PointLight {
+    /// Creates a new `PointLight` with the position, radius and premultiplied color.
+    pub fn new(position: Vec3, radius: f32, color: [f32; 3]) -> Self {
+        Self {
+            position: position.to_array(),
+            radius,
+            color,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+
+use crate::render_state::RenderState;
+
+/// Holds line vertex data that will be passed to the line shader. (Every two vertices make a line)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable, Default)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl LineVertex {
+    /// Creates a new `LineVertex` with the position and color.
+    pub fn new(position: Vec3, color: [f32; 4]) -> Self {
+        Self {
+            position: position.to_array(),
+            color,
+        }
+    }
+
+    /// The vertex attributes used by `wgpu`.
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+
+    /// Gets the `VertexBufferLayout` of `LineVertex`.
+    pub(crate) const fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// Creates the pipeline that draws `LineVertex` line lists on top of everything else.
+pub(crate) fn create_line_pipeline(
+    device: &wgpu::Device,
+    render_state: &RenderState,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Line Pipeline Layout"),
+        bind_group_layouts: &[render_state.get_bind_group_layouts()[0]],
+        push_constant_ranges: &[],
+    });
+
+    let shader = device.create_shader_module(wgpu::include_wgsl!("line_shader.wgsl"));
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Line Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_main"),
+            compilation_options: Default::default(),
+            buffers: &[LineVertex::layout()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_main"),
+            compilation_options: Default::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::LineList,
+            ..Default::default()
+        },
+        // Lines ignore the depth buffer so they're drawn on top of the sprites
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
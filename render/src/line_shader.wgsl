@@ -0,0 +1,31 @@
+struct Uniforms {
+    camera_view: mat4x4<f32>,
+    camera_projection: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(vertex: VertexInput) -> VertexOutput {
+    var result: VertexOutput;
+    result.position = uniforms.camera_projection * uniforms.camera_view * vec4<f32>(vertex.position, 1.0);
+    result.color = vertex.color;
+
+    return result;
+}
+
+@fragment
+fn fs_main(fragment: VertexOutput) -> @location(0) vec4<f32> {
+    return fragment.color;
+}
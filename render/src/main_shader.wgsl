@@ -1,6 +1,8 @@
 struct Uniforms {
     camera_view: mat4x4<f32>,
     camera_projection: mat4x4<f32>,
+    ambient_light: vec4<f32>,
+    light_count: u32,
 }
 
 @group(0) @binding(0)
@@ -8,22 +10,38 @@ var<uniform> uniforms: Uniforms;
 
 struct Instance {
     transform: mat3x4<f32>,
+    uv_rect: vec4<f32>,
+    color: vec4<f32>,
     texture_index: u32,
     sampler_index: u32,
+    normal_texture_index: u32,
 };
 
 @group(1) @binding(0)
 var<storage, read> instances: array<Instance>;
 
+struct PointLight {
+    position: vec3<f32>,
+    radius: f32,
+    color: vec3<f32>,
+};
+
+@group(1) @binding(1)
+var<storage, read> point_lights: array<PointLight>;
+
+const NO_NORMAL_MAP: u32 = 0xffffffffu;
+const LINEAR_NORMAL_MAP: u32 = 0x80000000u;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
-    @location(1) tex_coord: vec2<f32>,
+    @location(1) tex_coord: vec2<f32>, // (0, 0) is the top left corner, v points down
 }
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) @interpolate(flat) instance_index: u32,
     @location(1) tex_coord: vec2<f32>,
+    @location(2) world_position: vec3<f32>,
 };
 
 @vertex
@@ -39,9 +57,12 @@ fn vs_main(
         vec4<f32>(0.0, 0.0, 0.0, 1.0)
     ));
 
+    let world_position = instance_matrix * vec4<f32>(vertex.position, 1.0);
+
     var result: VertexOutput;
-    result.position = uniforms.camera_projection * uniforms.camera_view * instance_matrix * vec4<f32>(vertex.position, 1.0);
-    result.tex_coord = vertex.tex_coord;
+    result.position = uniforms.camera_projection * uniforms.camera_view * world_position;
+    result.world_position = world_position.xyz;
+    result.tex_coord = instance.uv_rect.xy + vertex.tex_coord * instance.uv_rect.zw;
     result.instance_index = instance_index;
 
     return result;
@@ -55,6 +76,7 @@ var sampler_array: binding_array<sampler>;
 struct FragmentInput {
     @location(0) @interpolate(flat) instance_index: u32,
     @location(1) tex_coord: vec2<f32>,
+    @location(2) world_position: vec3<f32>,
 }
 
 @fragment
@@ -64,9 +86,57 @@ fn fs_main(fragment: FragmentInput) -> @location(0) vec4<f32> {
         texture_array[instance.texture_index],
         sampler_array[instance.sampler_index],
         fragment.tex_coord,
-    );
+    ) * instance.color;
 
     if out.w < 0.5 { discard; } // Discard pixel if the texture alpha is transparent
 
-    return out;
+    return vec4<f32>(out.rgb * light(instance, fragment), out.a);
+}
+
+// Gets the light reaching the pixel from the ambient light and the point lights
+fn light(instance: Instance, fragment: FragmentInput) -> vec3<f32> {
+    var normal = vec3<f32>(0.0, 0.0, 1.0);
+    if instance.normal_texture_index != NO_NORMAL_MAP {
+        var encoded = textureSampleLevel(
+            texture_array[instance.normal_texture_index & ~LINEAR_NORMAL_MAP],
+            sampler_array[instance.sampler_index],
+            fragment.tex_coord,
+            0.0,
+        ).rgb;
+        // Normal maps in sRGB textures are converted back before decoding
+        if (instance.normal_texture_index & LINEAR_NORMAL_MAP) == 0u {
+            encoded = linear_to_srgb(encoded);
+        }
+        let tangent_normal = encoded * 2.0 - 1.0;
+
+        // Rotate the normal with the instance (the rows of the transposed matrix)
+        let x_axis = normalize(vec3<f32>(instance.transform[0].x, instance.transform[1].x, instance.transform[2].x));
+        let y_axis = normalize(vec3<f32>(instance.transform[0].y, instance.transform[1].y, instance.transform[2].y));
+        normal = normalize(x_axis * tangent_normal.x + y_axis * tangent_normal.y + vec3<f32>(0.0, 0.0, tangent_normal.z));
+    }
+
+    var result = uniforms.ambient_light.rgb;
+    for (var i = 0u; i < uniforms.light_count; i++) {
+        let point_light = point_lights[i];
+        let to_light = vec3<f32>(
+            point_light.position.xy - fragment.world_position.xy,
+            point_light.position.z,
+        );
+        let falloff = saturate(1.0 - length(to_light.xy) / point_light.radius);
+
+        var diffuse = 1.0;
+        if instance.normal_texture_index != NO_NORMAL_MAP {
+            diffuse = max(dot(normal, normalize(to_light)), 0.0);
+        }
+
+        result += point_light.color * falloff * falloff * diffuse;
+    }
+
+    return result;
+}
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
 }
@@ -0,0 +1,415 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+use crate::{gpu_timings::GpuProfiler, shader::Shader};
+
+/// The embedded source of the post-process bindings and full-screen vertex shader.
+const POST_PROCESS_SOURCE: &str = include_str!("post_process.wgsl");
+/// The embedded source of the built-in post-process effects.
+const POST_PROCESS_EFFECTS_SOURCE: &str = include_str!("post_process_effects.wgsl");
+
+/// A full-screen pass that is applied to the frame after the cameras are drawn.
+///
+/// The shader source is appended to `post_process.wgsl`, so it can use `source_texture`,
+/// `source_sampler`, the `post_process` uniforms and the `vs_fullscreen` entry point declared
+/// there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostProcess {
+    pub shader: Shader,
+    /// Effect specific values passed to the shader as `post_process.params`.
+    pub params: [f32; 4],
+}
+
+impl PostProcess {
+    /// Creates a new `PostProcess` with the shader and zeroed params.
+    pub fn new(shader: Shader) -> Self {
+        Self {
+            shader,
+            params: [0.0; 4],
+        }
+    }
+
+    /// Sets the values passed to the shader.
+    pub fn with_params(mut self, params: [f32; 4]) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Blends the frame towards grayscale. (0 is unchanged and 1 is fully gray)
+    pub fn grayscale(strength: f32) -> Self {
+        Self::new(Shader::new(POST_PROCESS_EFFECTS_SOURCE, "grayscale"))
+            .with_params([strength, 0.0, 0.0, 0.0])
+    }
+
+    /// Darkens the frame outside of `radius`, fading over `softness`. (Both relative to the
+    /// distance from the center to a corner)
+    pub fn vignette(strength: f32, radius: f32, softness: f32) -> Self {
+        Self::new(Shader::new(POST_PROCESS_EFFECTS_SOURCE, "vignette"))
+            .with_params([strength, radius, softness, 0.0])
+    }
+
+    /// Bends the frame like a CRT screen and darkens every other pixel row.
+    pub fn crt(curvature: f32, scanline_strength: f32) -> Self {
+        Self::new(Shader::new(POST_PROCESS_EFFECTS_SOURCE, "crt")).with_params([
+            curvature,
+            scanline_strength,
+            0.0,
+            0.0,
+        ])
+    }
+}
+
+/// Holds the uniform data of a post-process pass.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable, Default)]
+struct PostProcessUniforms {
+    params: [f32; 4],
+    resolution: [f32; 2],
+    _padding: [f32; 2],
+}
+
+/// A post-process and its last pipeline that compiled.
+struct PostProcessPass {
+    post_process: PostProcess,
+    /// `None` if the shader never compiled. (The pass is skipped)
+    pipeline: Option<wgpu::RenderPipeline>,
+    uniform_buffer: wgpu::Buffer,
+}
+
+/// Renders the scene into an intermediate texture, applies the post-process passes in order and
+/// blits the result to the window.
+pub(crate) struct PostProcessChain {
+    format: wgpu::TextureFormat,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    /// The textures the scene and passes are drawn to, alternating between passes.
+    targets: [wgpu::TextureView; 2],
+    blit_pipeline: wgpu::RenderPipeline,
+    blit_uniform_buffer: wgpu::Buffer,
+    passes: Vec<PostProcessPass>,
+}
+
+impl PostProcessChain {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post Process Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Process Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Process Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let blit_pipeline = Self::compile_pipeline(
+            device,
+            &pipeline_layout,
+            format,
+            POST_PROCESS_SOURCE,
+            "vs_fullscreen",
+            "fs_blit",
+        )
+        .expect("failed to compile the embedded blit shader");
+
+        Self {
+            format,
+            targets: Self::create_targets(device, format, width, height),
+            bind_group_layout,
+            pipeline_layout,
+            sampler,
+            blit_pipeline,
+            blit_uniform_buffer: Self::create_uniform_buffer(device),
+            passes: Vec::new(),
+        }
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> [wgpu::TextureView; 2] {
+        [(); 2].map(|_| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Post Process Texture"),
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_SRC,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        })
+    }
+
+    fn create_uniform_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post Process Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[PostProcessUniforms::default()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
+    /// Compiles a full-screen pipeline. Returns the validation error if the shader fails to
+    /// compile.
+    fn compile_pipeline(
+        device: &wgpu::Device,
+        pipeline_layout: &wgpu::PipelineLayout,
+        format: wgpu::TextureFormat,
+        source: &str,
+        vertex_entry_point: &str,
+        fragment_entry_point: &str,
+    ) -> Result<wgpu::RenderPipeline, wgpu::Error> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: Some(pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: Some(vertex_entry_point),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: Some(fragment_entry_point),
+                compilation_options: Default::default(),
+                targets: &[Some(format.into())],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(error) => Err(error),
+            None => Ok(pipeline),
+        }
+    }
+
+    /// Compiles a post-process shader by appending it to `post_process.wgsl`. Returns `None` and
+    /// reports the error if the shader can't be read or compiled.
+    fn compile_post_process(
+        &self,
+        device: &wgpu::Device,
+        shader: &Shader,
+    ) -> Option<wgpu::RenderPipeline> {
+        let source = shader
+            .read_source()
+            .inspect_err(|error| {
+                eprintln!(
+                    "failed to read post-process shader {}: {error}",
+                    shader.source
+                )
+            })
+            .ok()?;
+
+        Self::compile_pipeline(
+            device,
+            &self.pipeline_layout,
+            self.format,
+            &format!("{POST_PROCESS_SOURCE}\n{source}"),
+            shader
+                .vertex_entry_point
+                .as_deref()
+                .unwrap_or("vs_fullscreen"),
+            &shader.fragment_entry_point,
+        )
+        .inspect_err(|error| {
+            eprintln!(
+                "failed to compile post-process shader {}: {error}",
+                shader.source
+            )
+        })
+        .ok()
+    }
+
+    /// Recreates the intermediate textures with the new size.
+    pub(crate) fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.targets = Self::create_targets(device, self.format, width, height);
+    }
+
+    /// Replaces the passes, reusing the pipelines of unchanged shaders.
+    pub(crate) fn update_passes(&mut self, device: &wgpu::Device, post_processes: &[PostProcess]) {
+        let mut old_passes = std::mem::take(&mut self.passes);
+
+        for post_process in post_processes {
+            let pass = match old_passes
+                .iter()
+                .position(|old| old.post_process.shader == post_process.shader)
+            {
+                Some(i) => PostProcessPass {
+                    post_process: post_process.clone(),
+                    ..old_passes.swap_remove(i)
+                },
+                None => PostProcessPass {
+                    post_process: post_process.clone(),
+                    pipeline: self.compile_post_process(device, &post_process.shader),
+                    uniform_buffer: Self::create_uniform_buffer(device),
+                },
+            };
+
+            self.passes.push(pass);
+        }
+    }
+
+    /// Gets the texture the scene is drawn to.
+    pub(crate) fn get_scene_target(&self) -> &wgpu::TextureView {
+        &self.targets[0]
+    }
+
+    /// Gets the texture the final blit reads from, which holds the finished frame after
+    /// `encode()`.
+    pub(crate) fn get_output_target(&self) -> &wgpu::Texture {
+        let pass_count = self
+            .passes
+            .iter()
+            .filter(|pass| pass.pipeline.is_some())
+            .count();
+
+        self.targets[pass_count % 2].texture()
+    }
+
+    /// Encodes the post-process passes followed by the blit to `output`. Returns the number of
+    /// passes encoded.
+    pub(crate) fn encode(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        gpu_profiler: &mut GpuProfiler,
+        output: &wgpu::TextureView,
+        resolution: [f32; 2],
+    ) -> u32 {
+        let passes = self
+            .passes
+            .iter()
+            .filter_map(|pass| {
+                let pipeline = pass.pipeline.as_ref()?;
+                Some((pipeline, &pass.uniform_buffer, pass.post_process.params))
+            })
+            .chain([(&self.blit_pipeline, &self.blit_uniform_buffer, [0.0; 4])]);
+        let pass_count = self
+            .passes
+            .iter()
+            .filter(|pass| pass.pipeline.is_some())
+            .count();
+
+        for (i, (pipeline, uniform_buffer, params)) in passes.enumerate() {
+            let uniforms = PostProcessUniforms {
+                params,
+                resolution,
+                ..Default::default()
+            };
+            queue.write_buffer(uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post Process Bind Group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&self.targets[i % 2]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+            let target = if i == pass_count {
+                output
+            } else {
+                &self.targets[(i + 1) % 2]
+            };
+
+            let timestamp_writes = if i == pass_count {
+                gpu_profiler.timestamp_writes("blit")
+            } else {
+                gpu_profiler.timestamp_writes(format!("post process {i}"))
+            };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post Process Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        pass_count as u32 + 1
+    }
+}
@@ -0,0 +1,33 @@
+struct PostProcessUniforms {
+    params: vec4<f32>,
+    resolution: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> post_process: PostProcessUniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coord: vec2<f32>,
+};
+
+// Draws a triangle that covers the whole screen
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let tex_coord = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+
+    var result: VertexOutput;
+    result.position = vec4<f32>(tex_coord * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    result.tex_coord = tex_coord;
+
+    return result;
+}
+
+@fragment
+fn fs_blit(fragment: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(source_texture, source_sampler, fragment.tex_coord);
+}
@@ -0,0 +1,35 @@
+// params.x: strength
+@fragment
+fn grayscale(fragment: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(source_texture, source_sampler, fragment.tex_coord);
+    let luminance = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+
+    return vec4<f32>(mix(color.rgb, vec3<f32>(luminance), post_process.params.x), color.a);
+}
+
+// params.x: strength, params.y: radius, params.z: softness
+@fragment
+fn vignette(fragment: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(source_texture, source_sampler, fragment.tex_coord);
+    let distance = length(fragment.tex_coord - vec2<f32>(0.5)) * sqrt(2.0);
+    let radius = post_process.params.y;
+    let shade = smoothstep(radius, radius + post_process.params.z, distance);
+
+    return vec4<f32>(color.rgb * (1.0 - shade * post_process.params.x), color.a);
+}
+
+// params.x: curvature, params.y: scanline strength
+@fragment
+fn crt(fragment: VertexOutput) -> @location(0) vec4<f32> {
+    // Bend the screen outwards from the center
+    let centered = fragment.tex_coord * 2.0 - 1.0;
+    let bent = centered * (1.0 + post_process.params.x * dot(centered, centered));
+    let tex_coord = bent * 0.5 + 0.5;
+
+    let color = textureSample(source_texture, source_sampler, tex_coord);
+    let wave = sin(tex_coord.y * post_process.resolution.y * 3.14159265);
+    let scanline = 1.0 - post_process.params.y * (0.5 + 0.5 * wave);
+    let inside = all(tex_coord >= vec2<f32>(0.0)) && all(tex_coord <= vec2<f32>(1.0));
+
+    return select(vec4<f32>(0.0, 0.0, 0.0, 1.0), vec4<f32>(color.rgb * scanline, color.a), inside);
+}
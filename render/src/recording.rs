@@ -0,0 +1,100 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use crate::screenshot::{self, FrameImage};
+
+/// Where the frames of a recording are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordingOutput {
+    /// Numbered PNG files in the directory. (`frame_00000.png`, `frame_00001.png`, ...)
+    PngSequence(PathBuf),
+    /// The sRGB RGBA8 pixels of every frame appended to one file, rows from the top.
+    RawRgba(PathBuf),
+}
+
+/// A frame to record at the end of the frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordFrame {
+    pub output: RecordingOutput,
+    /// The frame number in the recording.
+    pub frame: u32,
+}
+
+/// Writes recorded frames once they're read back.
+#[derive(Default)]
+pub(crate) struct FrameRecorder {
+    /// The path and frame sender of the thread writing the current raw recording.
+    raw_writer: Option<(PathBuf, Sender<FrameImage>)>,
+}
+
+impl FrameRecorder {
+    /// Gets the function that writes the frame once it's read back.
+    pub(crate) fn on_read(&mut self, record_frame: &RecordFrame) -> Box<dyn FnOnce(FrameImage)> {
+        match &record_frame.output {
+            RecordingOutput::PngSequence(directory) => {
+                let directory = directory.clone();
+                let path = directory.join(format!("frame_{:05}.png", record_frame.frame));
+
+                Box::new(move |image| {
+                    if let Err(error) = fs::create_dir_all(&directory) {
+                        eprintln!("failed to create {}: {error}", directory.display());
+                        return;
+                    }
+                    screenshot::save_png_in_background(path, image);
+                })
+            }
+            RecordingOutput::RawRgba(path) => {
+                let sender = match &self.raw_writer {
+                    Some((raw_path, sender)) if raw_path == path => sender.clone(),
+                    _ => {
+                        let sender = spawn_raw_writer(path.clone());
+                        self.raw_writer = Some((path.clone(), sender.clone()));
+                        sender
+                    }
+                };
+
+                Box::new(move |image| {
+                    let _ = sender.send(image);
+                })
+            }
+        }
+    }
+
+    /// Closes the raw recording file once its remaining frames are written.
+    pub(crate) fn finish(&mut self) {
+        self.raw_writer = None;
+    }
+}
+
+/// Spawns a thread that appends the frames it receives to the file, in order.
+fn spawn_raw_writer(path: PathBuf) -> Sender<FrameImage> {
+    let (sender, receiver) = mpsc::channel::<FrameImage>();
+
+    thread::spawn(move || {
+        let mut file = match File::create(&path) {
+            Ok(file) => BufWriter::new(file),
+            Err(error) => {
+                eprintln!("failed to create {}: {error}", path.display());
+                return;
+            }
+        };
+
+        for image in receiver {
+            if let Err(error) = file.write_all(&image.data) {
+                eprintln!("failed to write to {}: {error}", path.display());
+                return;
+            }
+        }
+
+        if let Err(error) = file.flush() {
+            eprintln!("failed to write to {}: {error}", path.display());
+        }
+    });
+
+    sender
+}
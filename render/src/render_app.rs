@@ -11,7 +11,7 @@ use winit::{
     window::{Window, WindowAttributes, WindowId},
 };
 
-use crate::render_pipeline::RenderPipeline;
+use crate::{render_pipeline::RenderPipeline, shader::ShaderHotReload};
 
 /// Manages the winit event loop and `RenderPipeline`.
 pub struct RenderApp<T>
@@ -20,6 +20,8 @@ where
 {
     /// Initial window attributes.
     window_attributes: Option<WindowAttributes>,
+    /// Shader hot-reloading settings or `None` if disabled.
+    shader_hot_reload: Option<ShaderHotReload>,
     /// Function that runs when a frame is ready to be drawn (call `RenderPipeline::render()` to
     /// draw the frame).
     render: T,
@@ -38,6 +40,7 @@ where
     pub fn new(render: T) -> Self {
         Self {
             window_attributes: None,
+            shader_hot_reload: None,
             render,
             last_render: Instant::now(),
             render_pipeline: None,
@@ -50,6 +53,13 @@ where
         self
     }
 
+    /// Sets the shader hot-reloading settings. (Meant for development, shader files are checked
+    /// for modification twice a second)
+    pub fn with_shader_hot_reload(mut self, shader_hot_reload: Option<ShaderHotReload>) -> Self {
+        self.shader_hot_reload = shader_hot_reload;
+        self
+    }
+
     /// Runs the app and returns the winit event loop error if any occurs.
     pub fn run_app(&mut self) -> Result<(), EventLoopError> {
         let event_loop = EventLoop::new().unwrap();
@@ -75,7 +85,11 @@ where
 
             self.render_pipeline = Some((
                 window.clone(),
-                pollster::block_on(RenderPipeline::new(window.clone())).unwrap(),
+                pollster::block_on(RenderPipeline::new(
+                    window.clone(),
+                    self.shader_hot_reload.take(),
+                ))
+                .unwrap(),
             ));
 
             self.last_render = Instant::now(); // Set the instant right before the rendering starts
@@ -1,21 +1,38 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    fs,
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
 
 use glam::{Vec2, Vec3};
 use wgpu::util::DeviceExt;
 use winit::{dpi::PhysicalSize, window::Window};
 
 use crate::{
+    gpu_timings::{GpuProfiler, PassTiming},
+    hot_reload::{HOT_RELOAD_INTERVAL, modified_time},
+    line,
+    post_process::PostProcessChain,
+    recording::FrameRecorder,
     render_state::{
         MAX_BINDING_ARRAY_SAMPLERS, MAX_BINDING_ARRAY_TEXTURES, RenderState, UpdateRenderState,
     },
+    screenshot::{self, FrameReadback},
+    shader::{Shader, ShaderHotReload},
     vertex::Vertex,
 };
 
+/// The embedded source of the main shader.
+const MAIN_SHADER_SOURCE: &str = include_str!("main_shader.wgsl");
+
+/// A unit quad centered on the origin. Its texture coordinates start at the top left corner and V
+/// points down, like the rows of texture data.
 const QUAD_VERTICES: [Vertex; 4] = [
-    Vertex::new(Vec3::new(0.5, 0.5, 0.0), Vec2::new(1.0, 1.0)),
-    Vertex::new(Vec3::new(-0.5, 0.5, 0.0), Vec2::new(0.0, 1.0)),
-    Vertex::new(Vec3::new(0.5, -0.5, 0.0), Vec2::new(1.0, 0.0)),
-    Vertex::new(Vec3::new(-0.5, -0.5, 0.0), Vec2::new(0.0, 0.0)),
+    Vertex::new(Vec3::new(0.5, 0.5, 0.0), Vec2::new(1.0, 0.0)),
+    Vertex::new(Vec3::new(-0.5, 0.5, 0.0), Vec2::new(0.0, 0.0)),
+    Vertex::new(Vec3::new(0.5, -0.5, 0.0), Vec2::new(1.0, 1.0)),
+    Vertex::new(Vec3::new(-0.5, -0.5, 0.0), Vec2::new(0.0, 1.0)),
 ];
 const QUAD_INDICES: [u16; 6] = [0, 3, 2, 3, 0, 1];
 
@@ -29,23 +46,74 @@ pub struct RenderPipeline {
     quad_vertex_buffer: wgpu::Buffer,
     quad_index_buffer: wgpu::Buffer,
     depth_texture: wgpu::TextureView,
+    /// The depth textures of cameras that draw to a texture by size.
+    target_depth_textures: HashMap<(u32, u32), wgpu::TextureView>,
+    pipeline_layout: wgpu::PipelineLayout,
     pipeline: wgpu::RenderPipeline,
+    /// The source of the last main shader that compiled, custom shaders are appended to it.
+    main_shader_source: String,
+    /// The last modification time of the main shader file when hot-reloading.
+    main_shader_modified: Option<SystemTime>,
+    /// The custom shaders and their pipelines in the order of `UpdateRenderState::shaders`.
+    shader_pipelines: Vec<ShaderPipeline>,
+    shader_hot_reload: Option<ShaderHotReload>,
+    /// When the shader files were last checked for modification.
+    last_shader_poll: Instant,
+    line_pipeline: wgpu::RenderPipeline,
+    post_process_chain: PostProcessChain,
+    frame_readback: FrameReadback,
+    frame_recorder: FrameRecorder,
+    gpu_profiler: GpuProfiler,
+    render_stats: RenderStats,
     render_state: RenderState,
 }
 
+/// Counts of the work done to render the last frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// The number of instances in the instance buffers.
+    pub instance_count: u32,
+    /// The number of draw calls, including post-process passes.
+    pub draw_calls: u32,
+    /// The number of textures that can be drawn with.
+    pub texture_count: u32,
+    /// The number of buffers that were reallocated to fit more data.
+    pub buffer_reallocations: u32,
+}
+
+/// A custom shader and its last pipeline that compiled.
+struct ShaderPipeline {
+    shader: Shader,
+    /// `None` if the shader never compiled. (The main pipeline is used instead)
+    pipeline: Option<wgpu::RenderPipeline>,
+    /// The last modification time of the shader file when hot-reloading.
+    modified: Option<SystemTime>,
+}
+
 impl RenderPipeline {
-    /// Creates a new render pipeline that renders to the specified window.
-    pub(crate) async fn new(window: Arc<Window>) -> Option<Self> {
+    /// Creates a new render pipeline that renders to the specified window. Shader files are
+    /// watched for changes if `shader_hot_reload` is set.
+    pub(crate) async fn new(
+        window: Arc<Window>,
+        shader_hot_reload: Option<ShaderHotReload>,
+    ) -> Option<Self> {
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions::default())
             .await
             .ok()?;
+        // Passes are only timed and compressed textures are only sampled directly if the adapter
+        // supports it
+        let optional_features = adapter.features()
+            & (wgpu::Features::TIMESTAMP_QUERY
+                | wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::TEXTURE_COMPRESSION_ETC2);
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 required_features: wgpu::Features::TEXTURE_BINDING_ARRAY
                     | wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
-                    | wgpu::Features::PARTIALLY_BOUND_BINDING_ARRAY,
+                    | wgpu::Features::PARTIALLY_BOUND_BINDING_ARRAY
+                    | optional_features,
                 required_limits: wgpu::Limits {
                     max_binding_array_elements_per_shader_stage: MAX_BINDING_ARRAY_TEXTURES.get()
                         + MAX_BINDING_ARRAY_SAMPLERS.get(),
@@ -92,7 +160,8 @@ impl RenderPipeline {
             usage: wgpu::BufferUsages::INDEX,
         });
 
-        let depth_texture = Self::create_depth_texture(&device, &surface_config);
+        let depth_texture =
+            Self::create_depth_texture(&device, surface_config.width, surface_config.height);
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
@@ -100,22 +169,112 @@ impl RenderPipeline {
             push_constant_ranges: &[],
         });
 
-        let shader = device.create_shader_module(wgpu::include_wgsl!("main_shader.wgsl"));
+        let main_shader_path = shader_hot_reload
+            .as_ref()
+            .and_then(|shader_hot_reload| shader_hot_reload.main_shader_path.as_deref());
+        let main_shader_modified = main_shader_path.and_then(modified_time);
+        let (pipeline, main_shader_source) = main_shader_path
+            .and_then(|path| match fs::read_to_string(path) {
+                Ok(source) => Some(source),
+                Err(error) => {
+                    eprintln!("failed to read main shader {}: {error}", path.display());
+                    None
+                }
+            })
+            .and_then(|source| {
+                match Self::compile_pipeline(
+                    &device,
+                    &pipeline_layout,
+                    surface_config.view_formats[0],
+                    &source,
+                    "vs_main",
+                    "fs_main",
+                ) {
+                    Ok(pipeline) => Some((pipeline, source)),
+                    Err(error) => {
+                        eprintln!("failed to compile main shader: {error}");
+                        None
+                    }
+                }
+            })
+            .or_else(|| {
+                // Fall back to the embedded main shader
+                Self::compile_pipeline(
+                    &device,
+                    &pipeline_layout,
+                    surface_config.view_formats[0],
+                    MAIN_SHADER_SOURCE,
+                    "vs_main",
+                    "fs_main",
+                )
+                .ok()
+                .map(|pipeline| (pipeline, MAIN_SHADER_SOURCE.to_owned()))
+            })?;
+
+        let line_pipeline =
+            line::create_line_pipeline(&device, &render_state, surface_config.view_formats[0]);
+
+        let post_process_chain =
+            PostProcessChain::new(&device, surface_config.view_formats[0], width, height);
+        let gpu_profiler = GpuProfiler::new(&device, &queue);
 
+        Some(Self {
+            device,
+            queue,
+            window,
+            surface,
+            surface_config,
+            quad_vertex_buffer,
+            quad_index_buffer,
+            depth_texture,
+            target_depth_textures: HashMap::new(),
+            pipeline_layout,
+            pipeline,
+            main_shader_source,
+            main_shader_modified,
+            shader_pipelines: Vec::new(),
+            shader_hot_reload,
+            last_shader_poll: Instant::now(),
+            line_pipeline,
+            post_process_chain,
+            frame_readback: FrameReadback::default(),
+            frame_recorder: FrameRecorder::default(),
+            gpu_profiler,
+            render_stats: RenderStats::default(),
+            render_state,
+        })
+    }
+
+    /// Compiles the WGSL source into a render pipeline that draws instanced quads with the given
+    /// entry points. Returns the validation error if the shader fails to compile.
+    fn compile_pipeline(
+        device: &wgpu::Device,
+        pipeline_layout: &wgpu::PipelineLayout,
+        format: wgpu::TextureFormat,
+        source: &str,
+        vertex_entry_point: &str,
+        fragment_entry_point: &str,
+    ) -> Result<wgpu::RenderPipeline, wgpu::Error> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: None,
-            layout: Some(&pipeline_layout),
+            layout: Some(pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
+                module: &module,
+                entry_point: Some(vertex_entry_point),
                 compilation_options: Default::default(),
                 buffers: &[Vertex::layout()],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
+                module: &module,
+                entry_point: Some(fragment_entry_point),
                 compilation_options: Default::default(),
-                targets: &[Some(surface_config.view_formats[0].into())],
+                targets: &[Some(format.into())],
             }),
             primitive: wgpu::PrimitiveState {
                 cull_mode: Some(wgpu::Face::Back),
@@ -133,29 +292,112 @@ impl RenderPipeline {
             cache: None,
         });
 
-        Some(Self {
-            device,
-            queue,
-            window,
-            surface,
-            surface_config,
-            quad_vertex_buffer,
-            quad_index_buffer,
-            depth_texture,
-            pipeline,
-            render_state,
-        })
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(error) => Err(error),
+            None => Ok(pipeline),
+        }
     }
 
-    fn create_depth_texture(
-        device: &wgpu::Device,
-        surface_config: &wgpu::SurfaceConfiguration,
-    ) -> wgpu::TextureView {
+    /// Compiles a custom shader by appending it to the main shader. Returns `None` and reports the
+    /// error if the shader can't be read or compiled.
+    fn compile_shader_pipeline(&self, shader: &Shader) -> Option<wgpu::RenderPipeline> {
+        let source = shader
+            .read_source()
+            .inspect_err(|error| eprintln!("failed to read shader {}: {error}", shader.source))
+            .ok()?;
+
+        Self::compile_pipeline(
+            &self.device,
+            &self.pipeline_layout,
+            self.surface_config.view_formats[0],
+            &format!("{}\n{source}", self.main_shader_source),
+            shader.vertex_entry_point.as_deref().unwrap_or("vs_main"),
+            &shader.fragment_entry_point,
+        )
+        .inspect_err(|error| eprintln!("failed to compile shader {}: {error}", shader.source))
+        .ok()
+    }
+
+    /// Rebuilds the custom shader pipelines, reusing the pipelines of unchanged shaders.
+    fn update_shader_pipelines(&mut self, shaders: &[Shader]) {
+        let mut old_pipelines = std::mem::take(&mut self.shader_pipelines);
+
+        for shader in shaders {
+            let shader_pipeline = match old_pipelines.iter().position(|old| old.shader == *shader) {
+                Some(i) => old_pipelines.swap_remove(i),
+                None => ShaderPipeline {
+                    shader: shader.clone(),
+                    pipeline: self.compile_shader_pipeline(shader),
+                    modified: shader.modified_time(),
+                },
+            };
+
+            self.shader_pipelines.push(shader_pipeline);
+        }
+    }
+
+    /// Recompiles the main shader and custom shaders whose files were modified since the last
+    /// check, checking the files once every `HOT_RELOAD_INTERVAL`. Shaders that fail to compile
+    /// keep their last pipeline that compiled.
+    fn reload_shaders(&mut self) {
+        let Some(shader_hot_reload) = &self.shader_hot_reload else {
+            return;
+        };
+        if self.last_shader_poll.elapsed() < HOT_RELOAD_INTERVAL {
+            return;
+        }
+        self.last_shader_poll = Instant::now();
+
+        let mut main_shader_reloaded = false;
+        if let Some(path) = &shader_hot_reload.main_shader_path {
+            let modified = modified_time(path);
+            if modified != self.main_shader_modified {
+                self.main_shader_modified = modified;
+
+                match fs::read_to_string(path) {
+                    Ok(source) => match Self::compile_pipeline(
+                        &self.device,
+                        &self.pipeline_layout,
+                        self.surface_config.view_formats[0],
+                        &source,
+                        "vs_main",
+                        "fs_main",
+                    ) {
+                        Ok(pipeline) => {
+                            self.pipeline = pipeline;
+                            self.main_shader_source = source;
+                            main_shader_reloaded = true;
+                        }
+                        Err(error) => eprintln!("failed to compile main shader: {error}"),
+                    },
+                    Err(error) => {
+                        eprintln!("failed to read main shader {}: {error}", path.display())
+                    }
+                }
+            }
+        }
+
+        for i in 0..self.shader_pipelines.len() {
+            let modified = self.shader_pipelines[i].shader.modified_time();
+            // Custom shaders include the main shader so they're all recompiled when it changes
+            if main_shader_reloaded || modified != self.shader_pipelines[i].modified {
+                let pipeline = self.compile_shader_pipeline(&self.shader_pipelines[i].shader);
+
+                let shader_pipeline = &mut self.shader_pipelines[i];
+                shader_pipeline.modified = modified;
+                if pipeline.is_some() {
+                    shader_pipeline.pipeline = pipeline;
+                }
+            }
+        }
+    }
+
+    fn create_depth_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::TextureView {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Depth Texture"),
             size: wgpu::Extent3d {
-                width: surface_config.width,
-                height: surface_config.height,
+                width,
+                height,
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
@@ -176,7 +418,38 @@ impl RenderPipeline {
 
         self.surface.configure(&self.device, &self.surface_config);
 
-        self.depth_texture = Self::create_depth_texture(&self.device, &self.surface_config)
+        self.depth_texture = Self::create_depth_texture(
+            &self.device,
+            self.surface_config.width,
+            self.surface_config.height,
+        );
+        self.post_process_chain.resize(
+            &self.device,
+            self.surface_config.width,
+            self.surface_config.height,
+        );
+    }
+
+    /// Creates the depth textures of the cameras that draw to a texture and drops unused ones.
+    fn update_target_depth_textures(&mut self) {
+        let sizes: Vec<_> = self
+            .render_state
+            .get_cameras()
+            .iter()
+            .filter_map(|camera| self.render_state.get_texture(camera.target?))
+            .map(|texture| {
+                let size = texture.texture().size();
+                (size.width, size.height)
+            })
+            .collect();
+
+        self.target_depth_textures
+            .retain(|size, _| sizes.contains(size));
+        for (width, height) in sizes {
+            self.target_depth_textures
+                .entry((width, height))
+                .or_insert_with(|| Self::create_depth_texture(&self.device, width, height));
+        }
     }
 
     /// Uses the current `RenderState` to draw a frame to the window.
@@ -186,8 +459,20 @@ impl RenderPipeline {
             self.resize(inner_size);
         }
 
-        self.render_state
-            .update_render_state(&self.device, &self.queue, &update_render_state);
+        if let Some(shaders) = &update_render_state.shaders {
+            self.update_shader_pipelines(shaders);
+        }
+        self.reload_shaders();
+
+        if let Some(post_processes) = &update_render_state.post_processes {
+            self.post_process_chain
+                .update_passes(&self.device, post_processes);
+        }
+
+        let buffer_reallocations =
+            self.render_state
+                .update_render_state(&self.device, &self.queue, &update_render_state);
+        self.update_target_depth_textures();
 
         let surface_texture = self
             .surface
@@ -203,49 +488,221 @@ impl RenderPipeline {
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-        {
+        self.gpu_profiler.begin_frame();
+        // The window cameras draw to an intermediate texture so post-processes can be applied
+        let scene_view = self.post_process_chain.get_scene_target();
+        let mut window_drawn = false;
+        let mut draw_calls = 0;
+        for (camera_index, camera) in self.render_state.get_cameras().iter().enumerate() {
+            let (color_view, depth_view) = match camera.target {
+                Some(target) => {
+                    let Some(texture) = self.render_state.get_texture(target) else {
+                        continue;
+                    };
+                    let size = texture.texture().size();
+                    (
+                        texture,
+                        &self.target_depth_textures[&(size.width, size.height)],
+                    )
+                }
+                None => (scene_view, &self.depth_texture),
+            };
+
+            let timestamp_writes = self
+                .gpu_profiler
+                .timestamp_writes(format!("camera {camera_index}"));
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &surface_view,
+                    view: color_view,
                     depth_slice: None,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(update_render_state.clear_color),
+                        load: camera
+                            .clear_color
+                            .map_or(wgpu::LoadOp::Load, wgpu::LoadOp::Clear),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
+                // Every camera gets its own depth so overlays are drawn on top
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_texture,
+                    view: depth_view,
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Clear(1.0),
                         store: wgpu::StoreOp::Store,
                     }),
                     stencil_ops: None,
                 }),
-                timestamp_writes: None,
+                timestamp_writes,
                 occlusion_query_set: None,
             });
 
-            let instance_count = self.render_state.get_instance_count();
-            if instance_count > 0 {
-                render_pass.set_pipeline(&self.pipeline);
-                for (i, bind_group) in self.render_state.get_bind_groups().into_iter().enumerate() {
-                    render_pass.set_bind_group(i as u32, bind_group, &[]);
-                }
+            let camera_bind_group = self.render_state.get_camera_bind_group(camera_index);
+
+            if self.render_state.get_instance_count() > 0 {
+                render_pass.set_bind_group(0, camera_bind_group, &[]);
+                render_pass.set_bind_group(
+                    2,
+                    self.render_state.get_texture_bind_group(camera_index),
+                    &[],
+                );
 
                 render_pass
                     .set_index_buffer(self.quad_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
                 render_pass.set_vertex_buffer(0, self.quad_vertex_buffer.slice(..));
 
-                render_pass.draw_indexed(0..QUAD_INDICES.len() as u32, 0, 0..instance_count as u32);
+                // The main instances are drawn first, then the dynamic instances
+                for (instance_bind_group, batches) in self.render_state.get_instance_bind_groups() {
+                    render_pass.set_bind_group(1, instance_bind_group, &[]);
+
+                    for batch in batches
+                        .iter()
+                        .filter(|batch| batch.camera as usize == camera_index)
+                    {
+                        // Fall back to the main shader if the custom shader doesn't exist or
+                        // compile
+                        let pipeline = batch
+                            .shader
+                            .and_then(|i| self.shader_pipelines.get(i as usize))
+                            .and_then(|shader_pipeline| shader_pipeline.pipeline.as_ref())
+                            .unwrap_or(&self.pipeline);
+
+                        render_pass.set_pipeline(pipeline);
+                        render_pass.draw_indexed(
+                            0..QUAD_INDICES.len() as u32,
+                            0,
+                            batch.instances.clone(),
+                        );
+                        draw_calls += 1;
+                    }
+                }
+            }
+
+            // Lines are drawn by the first camera that draws to the window
+            let line_buffer = self.render_state.get_line_buffer();
+            if camera.target.is_none() && !window_drawn {
+                window_drawn = true;
+
+                if line_buffer.len() > 0 {
+                    render_pass.set_pipeline(&self.line_pipeline);
+                    render_pass.set_bind_group(0, camera_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, line_buffer.get_buffer().slice(..));
+
+                    render_pass.draw(0..line_buffer.len() as u32, 0..1);
+                    draw_calls += 1;
+                }
             }
         }
 
+        // Clear the frame even if no cameras draw to the window
+        if !window_drawn {
+            let timestamp_writes = self.gpu_profiler.timestamp_writes("clear");
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: scene_view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes,
+                occlusion_query_set: None,
+            });
+        }
+
+        draw_calls += self.post_process_chain.encode(
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &mut self.gpu_profiler,
+            &surface_view,
+            [
+                self.surface_config.width as f32,
+                self.surface_config.height as f32,
+            ],
+        );
+
+        for path in &update_render_state.screenshots {
+            let path = path.clone();
+            let captured = self.frame_readback.capture(
+                &self.device,
+                &mut encoder,
+                self.post_process_chain.get_output_target(),
+                Box::new(move |image| screenshot::save_png_in_background(path, image)),
+            );
+            if !captured {
+                eprintln!("failed to take screenshot: unsupported surface format");
+            }
+        }
+
+        match &update_render_state.record_frame {
+            Some(record_frame) => {
+                let captured = self.frame_readback.capture(
+                    &self.device,
+                    &mut encoder,
+                    self.post_process_chain.get_output_target(),
+                    self.frame_recorder.on_read(record_frame),
+                );
+                if !captured {
+                    eprintln!("failed to record frame: unsupported surface format");
+                }
+            }
+            None => self.frame_recorder.finish(),
+        }
+
+        self.gpu_profiler.resolve(&self.device, &mut encoder);
+
+        self.render_stats = RenderStats {
+            instance_count: self.render_state.get_instance_count() as u32,
+            draw_calls,
+            texture_count: self.render_state.get_texture_count() as u32,
+            buffer_reallocations,
+        };
+
         self.queue.submit(Some(encoder.finish()));
+        self.frame_readback.map_submitted();
+        self.gpu_profiler.map_submitted();
 
         self.window.pre_present_notify();
         surface_texture.present();
+
+        self.frame_readback.update(&self.device);
+        self.gpu_profiler.update(&self.device);
+    }
+
+    /// Takes the GPU timings of the latest frame that was read back since the last call. (Read
+    /// back a few frames late, always `None` if timestamp queries aren't supported)
+    pub fn take_gpu_timings(&mut self) -> Option<Vec<PassTiming>> {
+        self.gpu_profiler.take_timings()
+    }
+
+    /// Gets the counts of the work done to render the last frame.
+    pub fn get_render_stats(&self) -> RenderStats {
+        self.render_stats
+    }
+
+    /// Checks if the adapter supports timing render passes.
+    pub fn is_gpu_timing_supported(&self) -> bool {
+        self.gpu_profiler.is_supported()
+    }
+
+    /// Gets the features of the device, including the optional texture compression features.
+    pub fn get_features(&self) -> wgpu::Features {
+        self.device.features()
+    }
+
+    /// Gets the largest width and height of a texture the device supports.
+    pub fn get_max_texture_dimension(&self) -> u32 {
+        self.device.limits().max_texture_dimension_2d
+    }
+
+    /// Gets the texture format cameras draw with. (Textures that cameras draw to need this format)
+    pub fn get_target_format(&self) -> wgpu::TextureFormat {
+        self.surface_config.view_formats[0]
     }
 
     /// Gets the window `inner_size()`
@@ -274,3 +731,16 @@ impl RenderPipeline {
         self.device.create_sampler(sampler_descriptor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quad_texture_coordinates_start_at_the_top_left() {
+        for vertex in QUAD_VERTICES {
+            let [x, y, _] = vertex.position;
+            assert_eq!(vertex.texcoord, [x + 0.5, 0.5 - y]);
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use std::num::NonZeroU32;
+use std::{collections::HashMap, num::NonZeroU32, ops::Range, path::PathBuf};
 
-use crate::{array_buffer::ArrayBuffer, instance::Instance, uniforms::Uniforms};
+use crate::{
+    array_buffer::ArrayBuffer, instance::Instance, light::PointLight, line::LineVertex,
+    post_process::PostProcess, recording::RecordFrame, shader::Shader, uniforms::Uniforms,
+};
 use wgpu::util::DeviceExt;
 
 /// The maximum amount of textures allowed in the texture bind group.
@@ -10,18 +13,34 @@ pub(crate) const MAX_BINDING_ARRAY_SAMPLERS: NonZeroU32 = NonZeroU32::new(10).un
 
 /// Manages the buffers and bind groups for a `RenderPipeline`.
 pub(crate) struct RenderState {
-    uniform_buffer: wgpu::Buffer,
     uniform_bind_group_layout: wgpu::BindGroupLayout,
-    uniform_bind_group: wgpu::BindGroup,
+    cameras: Vec<CameraView>,
+    /// The uniform buffer and bind group of each camera.
+    camera_bind_groups: Vec<(wgpu::Buffer, wgpu::BindGroup)>,
     // --- //
     instance_buffer: ArrayBuffer<Instance>,
     instance_bind_group_layout: wgpu::BindGroupLayout,
     instance_bind_group: wgpu::BindGroup,
+    instance_batches: Vec<InstanceBatch>,
+    /// The instances that are replaced every frame, kept apart so the main instance buffer doesn't
+    /// need to be rewritten.
+    dynamic_instance_buffer: ArrayBuffer<Instance>,
+    dynamic_instance_bind_group: wgpu::BindGroup,
+    dynamic_instance_batches: Vec<InstanceBatch>,
+    light_buffer: ArrayBuffer<PointLight>,
     // --- //
     texture_bind_group_layout: wgpu::BindGroupLayout,
     texture_bind_group: wgpu::BindGroup,
+    textures: Vec<wgpu::TextureView>,
+    samplers: Vec<wgpu::Sampler>,
+    /// The texture bind groups used by cameras that draw to a texture, with the target texture
+    /// replaced by the dummy texture. (A texture can't be sampled while it's drawn to)
+    target_texture_bind_groups: HashMap<u32, wgpu::BindGroup>,
+    // --- //
+    line_buffer: ArrayBuffer<LineVertex>,
     // https://github.com/gfx-rs/wgpu/issues/3692
     dummy_instance: ArrayBuffer<Instance>,
+    dummy_light: ArrayBuffer<PointLight>,
     dummy_texture: wgpu::TextureView,
     dummy_sampler: wgpu::Sampler,
 }
@@ -31,22 +50,17 @@ impl RenderState {
     /// data.
     pub(crate) fn new(
         device: &wgpu::Device,
-        uniforms: Uniforms,
+        cameras: &[CameraView],
         instances: &[Instance],
         textures: &[wgpu::TextureView],
         samplers: &[wgpu::Sampler],
     ) -> Self {
-        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[uniforms]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
         let uniform_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("Uniform Bind Group Layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -57,14 +71,12 @@ impl RenderState {
                     count: None,
                 }],
             });
-        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Uniform Bind Group"),
-            layout: &uniform_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
-            }],
-        });
+        let camera_bind_groups = cameras
+            .iter()
+            .map(|camera| {
+                Self::create_camera_bind_group(device, &uniform_bind_group_layout, camera.uniforms)
+            })
+            .collect();
 
         let instance_buffer = ArrayBuffer::new(
             device,
@@ -72,39 +84,73 @@ impl RenderState {
             instances,
             wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
         );
+        let dynamic_instance_buffer = ArrayBuffer::new(
+            device,
+            Some("Dynamic Instance Buffer"),
+            &[],
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        );
         let dummy_instance = ArrayBuffer::new(
             device,
             None,
             &[Instance::default()],
             wgpu::BufferUsages::STORAGE,
         );
+        let light_buffer = ArrayBuffer::new(
+            device,
+            Some("Light Buffer"),
+            &[],
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        );
+        let dummy_light = ArrayBuffer::new(
+            device,
+            None,
+            &[PointLight::default()],
+            wgpu::BufferUsages::STORAGE,
+        );
         let instance_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("Instance Bind Group Layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
             });
-        let instance_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Instance Bind Group"),
-            layout: &instance_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                // https://github.com/gfx-rs/wgpu/issues/3692
-                resource: if instance_buffer.len() == 0 {
-                    dummy_instance.get_buffer().as_entire_binding()
-                } else {
-                    instance_buffer.get_buffer().as_entire_binding()
-                },
-            }],
-        });
+        let instance_bind_group = Self::create_instance_bind_group(
+            device,
+            &instance_bind_group_layout,
+            // https://github.com/gfx-rs/wgpu/issues/3692
+            if instance_buffer.len() == 0 {
+                &dummy_instance
+            } else {
+                &instance_buffer
+            },
+            &dummy_light,
+        );
+        let dynamic_instance_bind_group = Self::create_instance_bind_group(
+            device,
+            &instance_bind_group_layout,
+            &dummy_instance,
+            &dummy_light,
+        );
 
         let dummy_texture = device
             .create_texture(&wgpu::TextureDescriptor {
@@ -145,7 +191,107 @@ impl RenderState {
                     },
                 ],
             });
-        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        let texture_bind_group = Self::create_texture_bind_group(
+            device,
+            &texture_bind_group_layout,
+            &textures.iter().collect::<Vec<_>>(),
+            samplers,
+            &dummy_texture,
+            &dummy_sampler,
+        );
+
+        let line_buffer = ArrayBuffer::new(
+            device,
+            Some("Line Buffer"),
+            &[],
+            wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        );
+
+        Self {
+            uniform_bind_group_layout,
+            cameras: cameras.to_vec(),
+            camera_bind_groups,
+            instance_buffer,
+            instance_bind_group_layout,
+            instance_bind_group,
+            instance_batches: vec![InstanceBatch {
+                camera: 0,
+                shader: None,
+                instances: 0..instances.len() as u32,
+            }],
+            dynamic_instance_buffer,
+            dynamic_instance_bind_group,
+            dynamic_instance_batches: Vec::new(),
+            light_buffer,
+            texture_bind_group_layout,
+            texture_bind_group,
+            textures: textures.to_vec(),
+            samplers: samplers.to_vec(),
+            target_texture_bind_groups: HashMap::new(),
+            line_buffer,
+            dummy_instance,
+            dummy_light,
+            dummy_texture,
+            dummy_sampler,
+        }
+    }
+
+    /// Creates the uniform buffer and bind group of a camera.
+    fn create_camera_bind_group(
+        device: &wgpu::Device,
+        uniform_bind_group_layout: &wgpu::BindGroupLayout,
+        uniforms: Uniforms,
+    ) -> (wgpu::Buffer, wgpu::BindGroup) {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Uniform Bind Group"),
+            layout: uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        (uniform_buffer, uniform_bind_group)
+    }
+
+    /// Creates the bind group holding the instance and light buffers.
+    fn create_instance_bind_group(
+        device: &wgpu::Device,
+        instance_bind_group_layout: &wgpu::BindGroupLayout,
+        instance_buffer: &ArrayBuffer<Instance>,
+        light_buffer: &ArrayBuffer<PointLight>,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Instance Bind Group"),
+            layout: instance_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: instance_buffer.get_buffer().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: light_buffer.get_buffer().as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Creates the bind group holding the texture and sampler arrays.
+    fn create_texture_bind_group(
+        device: &wgpu::Device,
+        texture_bind_group_layout: &wgpu::BindGroupLayout,
+        textures: &[&wgpu::TextureView],
+        samplers: &[wgpu::Sampler],
+        dummy_texture: &wgpu::TextureView,
+        dummy_sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Texture Bind Group"),
             entries: &[
                 wgpu::BindGroupEntry {
@@ -153,9 +299,9 @@ impl RenderState {
                     resource: wgpu::BindingResource::TextureViewArray(
                         // https://github.com/gfx-rs/wgpu/issues/3692
                         &(if textures.is_empty() {
-                            vec![&dummy_texture]
+                            vec![dummy_texture]
                         } else {
-                            textures.iter().collect()
+                            textures.to_vec()
                         }),
                     ),
                 },
@@ -164,91 +310,164 @@ impl RenderState {
                     resource: wgpu::BindingResource::SamplerArray(
                         // https://github.com/gfx-rs/wgpu/issues/3692
                         &(if samplers.is_empty() {
-                            vec![&dummy_sampler]
+                            vec![dummy_sampler]
                         } else {
                             samplers.iter().collect()
                         }),
                     ),
                 },
             ],
-            layout: &texture_bind_group_layout,
-        });
-
-        Self {
-            uniform_buffer,
-            uniform_bind_group_layout,
-            uniform_bind_group,
-            instance_buffer,
-            instance_bind_group_layout,
-            instance_bind_group,
-            texture_bind_group_layout,
-            texture_bind_group,
-            dummy_instance,
-            dummy_texture,
-            dummy_sampler,
-        }
+            layout: texture_bind_group_layout,
+        })
     }
 
-    /// Updates the buffers and bind groups with the provided data.
+    /// Updates the buffers and bind groups with the provided data. Returns the number of buffers
+    /// that were reallocated to fit the data.
     pub(crate) fn update_render_state(
         &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         update_render_state: &UpdateRenderState,
-    ) {
-        if let Some(uniforms) = update_render_state.uniforms {
-            queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+    ) -> u32 {
+        let mut buffer_reallocations = 0;
+
+        if let Some(cameras) = &update_render_state.cameras {
+            self.camera_bind_groups.truncate(cameras.len());
+            for (i, camera) in cameras.iter().enumerate() {
+                match self.camera_bind_groups.get(i) {
+                    Some((uniform_buffer, _)) => queue.write_buffer(
+                        uniform_buffer,
+                        0,
+                        bytemuck::cast_slice(&[camera.uniforms]),
+                    ),
+                    None => self.camera_bind_groups.push(Self::create_camera_bind_group(
+                        device,
+                        &self.uniform_bind_group_layout,
+                        camera.uniforms,
+                    )),
+                }
+            }
+
+            self.cameras.clone_from(cameras);
         }
 
-        if let Some(instances) = &update_render_state.instances {
+        let mut instance_buffers_resized = false;
+        if let Some((instances, instance_batches)) = &update_render_state.instances {
+            self.instance_batches.clone_from(instance_batches);
+
             if self.instance_buffer.write_buffer(device, queue, instances) {
-                // Buffer was resized, remake the bind group
-                self.instance_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    label: Some("Instance Bind Group"),
-                    layout: &self.instance_bind_group_layout,
-                    entries: &[wgpu::BindGroupEntry {
-                        binding: 0,
-                        // https://github.com/gfx-rs/wgpu/issues/3692
-                        resource: if self.instance_buffer.len() == 0 {
-                            self.dummy_instance.get_buffer().as_entire_binding()
-                        } else {
-                            self.instance_buffer.get_buffer().as_entire_binding()
-                        },
-                    }],
-                });
+                instance_buffers_resized = true;
+                buffer_reallocations += 1;
+            }
+        }
+        for (offset, instances) in &update_render_state.instance_writes {
+            self.instance_buffer
+                .write_range(queue, *offset as usize, instances);
+        }
+
+        if let Some((instances, instance_batches)) = &update_render_state.dynamic_instances {
+            self.dynamic_instance_batches.clone_from(instance_batches);
+
+            if self
+                .dynamic_instance_buffer
+                .write_buffer(device, queue, instances)
+            {
+                instance_buffers_resized = true;
+                buffer_reallocations += 1;
             }
         }
 
+        if let Some(lights) = &update_render_state.lights
+            && self.light_buffer.write_buffer(device, queue, lights)
+        {
+            instance_buffers_resized = true;
+            buffer_reallocations += 1;
+        }
+
+        if instance_buffers_resized {
+            // Buffer was resized, remake the bind groups
+            // https://github.com/gfx-rs/wgpu/issues/3692
+            let light_buffer = if self.light_buffer.get_buffer().size() == 0 {
+                &self.dummy_light
+            } else {
+                &self.light_buffer
+            };
+            self.instance_bind_group = Self::create_instance_bind_group(
+                device,
+                &self.instance_bind_group_layout,
+                if self.instance_buffer.get_buffer().size() == 0 {
+                    &self.dummy_instance
+                } else {
+                    &self.instance_buffer
+                },
+                light_buffer,
+            );
+            self.dynamic_instance_bind_group = Self::create_instance_bind_group(
+                device,
+                &self.instance_bind_group_layout,
+                if self.dynamic_instance_buffer.get_buffer().size() == 0 {
+                    &self.dummy_instance
+                } else {
+                    &self.dynamic_instance_buffer
+                },
+                light_buffer,
+            );
+        }
+
+        if let Some(lines) = &update_render_state.lines
+            && self.line_buffer.write_buffer(device, queue, lines)
+        {
+            buffer_reallocations += 1;
+        }
+
         if let Some((textures, samplers)) = &update_render_state.textures {
-            self.texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Texture Bind Group"),
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureViewArray(
-                            // https://github.com/gfx-rs/wgpu/issues/3692
-                            &(if textures.is_empty() {
-                                vec![&self.dummy_texture]
-                            } else {
-                                textures.iter().collect()
-                            }),
-                        ),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::SamplerArray(
-                            // https://github.com/gfx-rs/wgpu/issues/3692
-                            &(if samplers.is_empty() {
-                                vec![&self.dummy_sampler]
-                            } else {
-                                samplers.iter().collect()
-                            }),
-                        ),
-                    },
-                ],
-                layout: &self.texture_bind_group_layout,
-            });
+            self.texture_bind_group = Self::create_texture_bind_group(
+                device,
+                &self.texture_bind_group_layout,
+                &textures.iter().collect::<Vec<_>>(),
+                samplers,
+                &self.dummy_texture,
+                &self.dummy_sampler,
+            );
+            self.textures.clone_from(textures);
+            self.samplers.clone_from(samplers);
+            self.target_texture_bind_groups.clear();
         }
+
+        for camera in &self.cameras {
+            let Some(target) = camera.target else {
+                continue;
+            };
+            if self.target_texture_bind_groups.contains_key(&target)
+                || target as usize >= self.textures.len()
+            {
+                continue;
+            }
+
+            let textures: Vec<_> = self
+                .textures
+                .iter()
+                .enumerate()
+                .map(|(i, texture)| {
+                    if i == target as usize {
+                        &self.dummy_texture
+                    } else {
+                        texture
+                    }
+                })
+                .collect();
+            let bind_group = Self::create_texture_bind_group(
+                device,
+                &self.texture_bind_group_layout,
+                &textures,
+                &self.samplers,
+                &self.dummy_texture,
+                &self.dummy_sampler,
+            );
+            self.target_texture_bind_groups.insert(target, bind_group);
+        }
+
+        buffer_reallocations
     }
 
     /// Gets the bind group layouts in order.
@@ -260,26 +479,104 @@ impl RenderState {
         ]
     }
 
-    /// Gets the bind groups in order.
-    pub(crate) fn get_bind_groups(&self) -> [&wgpu::BindGroup; 3] {
+    /// Gets the cameras in draw order.
+    pub(crate) fn get_cameras(&self) -> &[CameraView] {
+        &self.cameras
+    }
+
+    /// Gets the uniform bind group of a camera (bind group 0).
+    pub(crate) fn get_camera_bind_group(&self, camera: usize) -> &wgpu::BindGroup {
+        &self.camera_bind_groups[camera].1
+    }
+
+    /// Gets the instance and light bind group (bind group 1) of the main and dynamic instances
+    /// with the instance batches that divide them by shader.
+    pub(crate) fn get_instance_bind_groups(&self) -> [(&wgpu::BindGroup, &[InstanceBatch]); 2] {
         [
-            &self.uniform_bind_group,
-            &self.instance_bind_group,
-            &self.texture_bind_group,
+            (&self.instance_bind_group, &self.instance_batches),
+            (
+                &self.dynamic_instance_bind_group,
+                &self.dynamic_instance_batches,
+            ),
         ]
     }
 
-    /// Gets the amount of instances currently in the instance buffer.
+    /// Gets the texture bind group of a camera (bind group 2).
+    pub(crate) fn get_texture_bind_group(&self, camera: usize) -> &wgpu::BindGroup {
+        self.cameras[camera]
+            .target
+            .and_then(|target| self.target_texture_bind_groups.get(&target))
+            .unwrap_or(&self.texture_bind_group)
+    }
+
+    /// Gets a texture view by its index in `UpdateRenderState::textures`.
+    pub(crate) fn get_texture(&self, texture: u32) -> Option<&wgpu::TextureView> {
+        self.textures.get(texture as usize)
+    }
+
+    /// Gets the amount of textures that can be drawn with.
+    pub(crate) fn get_texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Gets the amount of instances currently in the main and dynamic instance buffers.
     pub(crate) fn get_instance_count(&self) -> usize {
-        self.instance_buffer.len()
+        self.instance_buffer.len() + self.dynamic_instance_buffer.len()
+    }
+
+    /// Gets the buffer holding the line vertices.
+    pub(crate) fn get_line_buffer(&self) -> &ArrayBuffer<LineVertex> {
+        &self.line_buffer
     }
 }
 
+/// A camera that draws its instance batches in a separate render pass.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CameraView {
+    pub uniforms: Uniforms,
+    /// The color to clear the frame with or `None` to draw over the previous cameras.
+    pub clear_color: Option<wgpu::Color>,
+    /// The index of the texture in `UpdateRenderState::textures` to draw to or `None` to draw to
+    /// the window. (The texture needs `RENDER_ATTACHMENT` usage and the target format)
+    pub target: Option<u32>,
+}
+
+/// A contiguous range of instances in the instance buffer that are drawn with the same camera and
+/// shader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceBatch {
+    /// The index of the camera in `UpdateRenderState::cameras`.
+    pub camera: u32,
+    /// The index of the shader in `UpdateRenderState::shaders` or `None` for the main shader.
+    pub shader: Option<u32>,
+    /// The range of instances in the instance buffer.
+    pub instances: Range<u32>,
+}
+
 /// Used to update a `RenderState` with new data. Any `None` fields will be left untouched.
 #[derive(Debug, Clone, Default)]
 pub struct UpdateRenderState {
-    pub clear_color: wgpu::Color,
-    pub uniforms: Option<Uniforms>,
-    pub instances: Option<Vec<Instance>>,
+    /// The cameras in draw order.
+    pub cameras: Option<Vec<CameraView>>,
+    /// The instances sorted by camera and shader and the batches that divide them.
+    pub instances: Option<(Vec<Instance>, Vec<InstanceBatch>)>,
+    /// Instances that replace part of the instance buffer as `(first instance, instances)`, written
+    /// after `instances`.
+    pub instance_writes: Vec<(u32, Vec<Instance>)>,
+    /// Instances that change every frame in their own buffer, so updating them doesn't rewrite
+    /// `instances`. (The batch ranges index into these instances)
+    pub dynamic_instances: Option<(Vec<Instance>, Vec<InstanceBatch>)>,
     pub textures: Option<(Vec<wgpu::TextureView>, Vec<wgpu::Sampler>)>,
+    /// The custom shaders that instance batches can reference by index.
+    pub shaders: Option<Vec<Shader>>,
+    /// The point lights the cameras shade with. (See `Uniforms::light_count`)
+    pub lights: Option<Vec<PointLight>>,
+    /// The line list drawn on top of the instances of the first camera.
+    pub lines: Option<Vec<LineVertex>>,
+    /// The post-process passes applied to the frame in order.
+    pub post_processes: Option<Vec<PostProcess>>,
+    /// The paths to save PNG screenshots of this frame to.
+    pub screenshots: Vec<PathBuf>,
+    /// Records this frame, `None` ends the current recording.
+    pub record_frame: Option<RecordFrame>,
 }
@@ -0,0 +1,195 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::Path,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+/// A frame read back from the GPU.
+#[derive(Debug, Clone)]
+pub(crate) struct FrameImage {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// The sRGB RGBA8 pixels row by row from the top.
+    pub(crate) data: Vec<u8>,
+}
+
+/// A texture copy waiting for its buffer to be mapped.
+struct PendingFrame {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    /// If the red and blue channels need to be swapped.
+    bgra: bool,
+    /// `None` until `map_async()` is called after the copy is submitted.
+    mapped: Option<Receiver<Result<(), wgpu::BufferAsyncError>>>,
+    on_read: Box<dyn FnOnce(FrameImage)>,
+}
+
+/// Copies frames into buffers and reads them back once they're mapped, without waiting for the
+/// GPU.
+#[derive(Default)]
+pub(crate) struct FrameReadback {
+    pending: Vec<PendingFrame>,
+}
+
+impl FrameReadback {
+    /// Encodes a copy of the texture and calls `on_read` with the pixels once they're read back.
+    /// Returns `false` and doesn't copy if the texture format isn't 8-bit RGBA or BGRA.
+    pub(crate) fn capture(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        on_read: Box<dyn FnOnce(FrameImage)>,
+    ) -> bool {
+        let bgra = match texture.format() {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            _ => return false,
+        };
+
+        let size = texture.size();
+        // Buffer rows have to be aligned
+        let padded_bytes_per_row =
+            (size.width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: padded_bytes_per_row as u64 * size.height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                depth_or_array_layers: 1,
+                ..size
+            },
+        );
+
+        self.pending.push(PendingFrame {
+            buffer,
+            width: size.width,
+            height: size.height,
+            padded_bytes_per_row,
+            bgra,
+            mapped: None,
+            on_read,
+        });
+
+        true
+    }
+
+    /// Starts mapping the buffers of the copies that were just submitted.
+    pub(crate) fn map_submitted(&mut self) {
+        for frame in &mut self.pending {
+            if frame.mapped.is_none() {
+                let (sender, receiver) = mpsc::channel();
+                frame
+                    .buffer
+                    .slice(..)
+                    .map_async(wgpu::MapMode::Read, move |result| {
+                        let _ = sender.send(result);
+                    });
+                frame.mapped = Some(receiver);
+            }
+        }
+    }
+
+    /// Checks the device without blocking and reads back the frames whose buffers were mapped.
+    pub(crate) fn update(&mut self, device: &wgpu::Device) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        if let Err(error) = device.poll(wgpu::PollType::Poll) {
+            eprintln!("failed to poll the device: {error}");
+        }
+
+        // Frames are read back in order so sequences stay ordered
+        while let Some(frame) = self.pending.first() {
+            let Some(mapped) = &frame.mapped else {
+                break;
+            };
+
+            match mapped.try_recv() {
+                Ok(result) => {
+                    let frame = self.pending.remove(0);
+                    match result {
+                        Ok(()) => frame.read(),
+                        Err(error) => eprintln!("failed to read back frame: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.pending.remove(0);
+                    eprintln!("failed to read back frame: the buffer mapping was dropped");
+                }
+            }
+        }
+    }
+}
+
+impl PendingFrame {
+    /// Removes the row padding, converts BGRA to RGBA and calls `on_read`.
+    fn read(self) {
+        let row_bytes = self.width as usize * 4;
+        let mut data = Vec::with_capacity(row_bytes * self.height as usize);
+        {
+            let mapped = self.buffer.slice(..).get_mapped_range();
+            for row in mapped.chunks_exact(self.padded_bytes_per_row as usize) {
+                data.extend_from_slice(&row[..row_bytes]);
+            }
+        }
+        self.buffer.unmap();
+
+        if self.bgra {
+            for pixel in data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        (self.on_read)(FrameImage {
+            width: self.width,
+            height: self.height,
+            data,
+        });
+    }
+}
+
+/// Saves the frame as a PNG file on a worker thread.
+pub(crate) fn save_png_in_background(path: impl AsRef<Path> + Send + 'static, image: FrameImage) {
+    thread::spawn(move || {
+        let path = path.as_ref();
+        if let Err(error) = save_png(path, &image) {
+            eprintln!("failed to save {}: {error}", path.display());
+        }
+    });
+}
+
+/// Saves the frame as a PNG file.
+pub(crate) fn save_png(path: &Path, image: &FrameImage) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        image.width,
+        image.height,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&image.data)?;
+    writer.finish()
+}
@@ -0,0 +1,89 @@
+use std::{borrow::Cow, fmt, fs, io, path::PathBuf, time::SystemTime};
+
+use crate::hot_reload;
+
+/// A user-provided WGSL shader that can be selected per instance batch.
+///
+/// The source is appended to `main_shader.wgsl`, so it can use the bindings, structs and the
+/// `vs_main` entry point declared there. (Don't redeclare `vs_main` or `fs_main`)
+///
+/// The `tex_coord` from `vs_main` starts at the top left of the material's uv rect and V points
+/// down.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Shader {
+    /// The WGSL source code.
+    pub source: ShaderSource,
+    /// The vertex entry point or `None` to use `vs_main` from the main shader.
+    pub vertex_entry_point: Option<String>,
+    /// The fragment entry point.
+    pub fragment_entry_point: String,
+}
+
+impl Shader {
+    /// Creates a new `Shader` with the WGSL source and fragment entry point.
+    pub fn new(source: impl Into<String>, fragment_entry_point: impl Into<String>) -> Self {
+        Self {
+            source: ShaderSource::Wgsl(source.into()),
+            vertex_entry_point: None,
+            fragment_entry_point: fragment_entry_point.into(),
+        }
+    }
+
+    /// Creates a new `Shader` that loads its WGSL source from a file. (The file is watched for
+    /// changes when shader hot-reloading is enabled)
+    pub fn from_path(path: impl Into<PathBuf>, fragment_entry_point: impl Into<String>) -> Self {
+        Self {
+            source: ShaderSource::Path(path.into()),
+            vertex_entry_point: None,
+            fragment_entry_point: fragment_entry_point.into(),
+        }
+    }
+
+    /// Sets the vertex entry point.
+    pub fn with_vertex_entry_point(mut self, vertex_entry_point: impl Into<String>) -> Self {
+        self.vertex_entry_point = Some(vertex_entry_point.into());
+        self
+    }
+
+    /// Gets the WGSL source code, reading it from disk if the shader is path-backed.
+    pub(crate) fn read_source(&self) -> io::Result<Cow<'_, str>> {
+        match &self.source {
+            ShaderSource::Wgsl(source) => Ok(Cow::Borrowed(source)),
+            ShaderSource::Path(path) => fs::read_to_string(path).map(Cow::Owned),
+        }
+    }
+
+    /// Gets the last modification time of the shader file or `None` if the shader isn't
+    /// path-backed.
+    pub(crate) fn modified_time(&self) -> Option<SystemTime> {
+        match &self.source {
+            ShaderSource::Wgsl(_) => None,
+            ShaderSource::Path(path) => hot_reload::modified_time(path),
+        }
+    }
+}
+
+/// Where the WGSL source code of a `Shader` comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ShaderSource {
+    /// Inline WGSL source code.
+    Wgsl(String),
+    /// A path to a WGSL file.
+    Path(PathBuf),
+}
+
+impl fmt::Display for ShaderSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderSource::Wgsl(_) => f.write_str("<inline>"),
+            ShaderSource::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Settings for recompiling shaders between frames when their files are modified.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderHotReload {
+    /// Loads the main shader from this path instead of the embedded `main_shader.wgsl`.
+    pub main_shader_path: Option<PathBuf>,
+}
@@ -7,14 +7,28 @@ use glam::Mat4;
 pub struct Uniforms {
     pub camera_view: [f32; 16],
     pub camera_projection: [f32; 16],
+    /// The linear RGB ambient light color. (Alpha is unused)
+    pub ambient_light: [f32; 4],
+    /// The amount of point lights in the light buffer that affect this camera.
+    pub light_count: u32,
+    _padding: [u32; 3],
 }
 
 impl Uniforms {
-    /// Creates a new `Uniforms` with the camera view and projection.
+    /// Creates a new `Uniforms` with the camera view and projection. (Unlit by default)
     pub fn new(camera_view: Mat4, camera_projection: Mat4) -> Self {
         Self {
             camera_view: camera_view.inverse().to_cols_array(),
             camera_projection: camera_projection.to_cols_array(),
+            ambient_light: [1.0; 4],
+            ..Default::default()
         }
     }
+
+    /// Sets the ambient light and the amount of point lights to shade with.
+    pub fn with_lighting(mut self, ambient_light: [f32; 4], light_count: u32) -> Self {
+        self.ambient_light = ambient_light;
+        self.light_count = light_count;
+        self
+    }
 }
@@ -16,20 +16,15 @@ fn spawn_test_stuff(mut commands: Commands, mut textures: ResMut<Textures>) {
             b: 0.3,
             a: 1.0,
         },
+        target: None,
     });
 
     let samplers = textures.get_samplers_mut();
     let sampler = samplers.push(Sampler);
 
     let textures = textures.get_textures_mut();
-    let red_texture = textures.push(Texture {
-        size: (1, 1),
-        data: vec![255, 0, 0, 255],
-    });
-    let blue_texture = textures.push(Texture {
-        size: (1, 1),
-        data: vec![0, 0, 255, 255],
-    });
+    let red_texture = textures.push(Texture::new((1, 1), vec![255, 0, 0, 255]));
+    let blue_texture = textures.push(Texture::new((1, 1), vec![0, 0, 255, 255]));
 
     commands.spawn((
         Transform::IDENTITY,